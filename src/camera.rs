@@ -4,6 +4,9 @@ use specs::prelude::*;
 
 const SHOW_BOUNDARIES: bool = true;
 
+/// Even the dimmest lit tile keeps this much of its own color, so it stays readable
+const MIN_BRIGHTNESS: f32 = 0.35;
+
 pub fn get_screen_bounds(ecs: &World, ctx: &Rltk) -> (i32, i32, i32, i32) {
     let player_pos = ecs.fetch::<Point>();
     let (x_chars, y_chars) = ctx.get_char_size();
@@ -75,7 +78,7 @@ pub fn render_camera(ecs: &World, ctx: &mut Rltk) {
                 ctx.set(
                     entity_screen_x,
                     entity_screen_y,
-                    render.fg,
+                    apply_light(render.fg, map.light[idx]),
                     render.bg,
                     render.glyph,
                 );
@@ -149,7 +152,10 @@ fn get_tile_glyph(idx: usize, map: &Map) -> (rltk::FontCharType, RGB, RGB) {
     } else {
         RGB::from_f32(0., 0., 0.)
     };
-    if !map.visible_tiles[idx] {
+    if map.visible_tiles[idx] {
+        fg = apply_light(fg, map.light[idx]);
+        bg = apply_light(bg, map.light[idx]);
+    } else {
         fg = fg.to_greyscale();
         bg = RGB::from_f32(0., 0., 0.); // Don't show stains out of visual range
     }
@@ -157,6 +163,16 @@ fn get_tile_glyph(idx: usize, map: &Map) -> (rltk::FontCharType, RGB, RGB) {
     (glyph, fg, bg)
 }
 
+/// Tints a color by the light falling on it
+fn apply_light(color: RGB, light: RGB) -> RGB {
+    let shade = |c: f32, l: f32| c * (MIN_BRIGHTNESS + (1.0 - MIN_BRIGHTNESS) * l);
+    RGB::from_f32(
+        shade(color.r, light.r),
+        shade(color.g, light.g),
+        shade(color.b, light.b),
+    )
+}
+
 fn wall_glyph(map: &Map, x: i32, y: i32) -> rltk::FontCharType {
     if x < 1 || x > map.width - 2 || y < 1 || y > map.height - 2 {
         return 35;
@@ -4,6 +4,7 @@ use specs::error::NoError;
 use specs::prelude::*;
 use specs::saveload::{ConvertSaveload, Marker};
 use specs_derive::*;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Component, ConvertSaveload, Clone)]
 pub struct Position {
@@ -32,6 +33,44 @@ pub struct Viewshed {
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct Monster {}
 
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum AiState {
+    Sleeping,
+    Idle,
+    Wandering { destination: usize },
+    Chasing { x: i32, y: i32 },
+    Searching { x: i32, y: i32, turns: i32 },
+    Fleeing,
+}
+
+impl AiState {
+    /// Monsters going about their business haven't noticed anyone yet
+    pub fn is_unaware(&self) -> bool {
+        matches!(
+            self,
+            AiState::Sleeping | AiState::Idle | AiState::Wandering { .. }
+        )
+    }
+}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Behavior {
+    pub state: AiState,
+    pub wanders: bool,
+    pub flee_below_percent: i32,
+    pub perception: i32,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct KeepsDistance {
+    pub range: i32,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Faction {
+    pub name: String,
+}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct Name {
     pub name: String,
@@ -48,6 +87,35 @@ pub struct Door {
     pub open: bool,
 }
 
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct OpensDoors {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct UsesItems {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Boss {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct BashesDoors {
+    pub strength: i32,
+}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToOpenDoor {
+    pub door: Entity,
+}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToCloseDoor {
+    pub door: Entity,
+}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToBashDoor {
+    pub door: Entity,
+}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct CombatStats {
     pub max_hp: i32,
@@ -56,6 +124,24 @@ pub struct CombatStats {
     pub power: i32,
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum AttackEffect {
+    Confusion { turns: i32 },
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct NaturalAttack {
+    pub name: String,
+    pub damage: i32,
+    pub hit_bonus: i32,
+    pub effect: Option<AttackEffect>,
+}
+
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct NaturalAttacks {
+    pub attacks: Vec<NaturalAttack>,
+}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct WantsToMelee {
     pub target: Entity,
@@ -110,6 +196,21 @@ pub struct ProvidesHealing {
     pub heal_amount: i32,
 }
 
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct PackMember {
+    pub leader: Entity,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Routed {}
+
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct Ability {
+    pub owner: Entity,
+    pub cooldown: i32,
+    pub cooldown_remaining: i32,
+}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct InBackpack {
     pub owner: Entity,
@@ -132,6 +233,49 @@ pub struct WantsToDropItem {
     pub item: Entity,
 }
 
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct WantsToThrowItem {
+    pub item: Entity,
+    pub target: rltk::Point,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum TeleportKind {
+    /// Somewhere at random on the same level
+    Random,
+    /// To a visible tile of the user's choosing
+    Blink,
+    /// To another depth entirely
+    Level,
+}
+
+/// Moves whoever uses it, or steps on it, somewhere else
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Teleports {
+    pub kind: TeleportKind,
+}
+
+/// Blinks need a `destination`; the other kinds pick their own
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct WantsToTeleport {
+    pub kind: TeleportKind,
+    pub destination: Option<rltk::Point>,
+}
+
+/// Sheds light around it. Items only shine while equipped; anything else (a glowing
+/// monster, say) lights up wherever it stands.
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct LightSource {
+    pub color: RGB,
+    pub range: i32,
+}
+
+/// Turns of light left in a torch or lantern; it burns only while equipped
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Fuel {
+    pub turns: i32,
+}
+
 #[derive(Component, Debug, ConvertSaveload, Clone)]
 pub struct WantsToRemoveItem {
     pub item: Entity,
@@ -141,6 +285,7 @@ pub struct WantsToRemoveItem {
 pub enum EquipmentSlot {
     Melee,
     Shield,
+    Light,
 }
 
 #[derive(Component, Serialize, Deserialize, Clone)]
@@ -174,18 +319,335 @@ pub enum HungerState {
     Starving,
 }
 
+/// The most anyone can eat; the hunger meter never fills past this
+pub const MAX_NUTRITION: i32 = 1000;
+
+impl HungerState {
+    /// How hungry someone with this much nutrition left in them feels
+    pub fn from_nutrition(nutrition: i32) -> HungerState {
+        if nutrition > 600 {
+            HungerState::WellFed
+        } else if nutrition > 300 {
+            HungerState::Normal
+        } else if nutrition > 0 {
+            HungerState::Hungry
+        } else {
+            HungerState::Starving
+        }
+    }
+}
+
+/// A hunger meter that drains by one nutrition a turn; `state` is kept in step with it
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct HungerClock {
     pub state: HungerState,
-    pub duration: i32,
+    pub nutrition: i32,
 }
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
-pub struct ProvidesFood {}
+pub struct ProvidesFood {
+    pub nutrition: i32,
+}
+
+/// Food that goes off. Once `turns` runs out it's rotten: it feeds you half as well and may
+/// well make you ill.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Perishable {
+    pub turns: i32,
+}
+
+impl Perishable {
+    pub fn rotten(&self) -> bool {
+        self.turns <= 0
+    }
+}
+
+/// Made ill by bad food: hunger comes on faster, and wounds won't heal
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Sickness {
+    pub turns: i32,
+}
 
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct MagicMapper {}
 
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct IdentifiesItem {}
+
+/// Cursed equipment can't be taken off once it has been put on
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Cursed {}
+
+/// The player knows whether or not this item is cursed
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct CurseKnown {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct RemovesCurse {}
+
+/// Reusable items (wands and the like) that work a limited number of times before going inert
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Charges {
+    pub current: i32,
+    pub max: i32,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Recharges {}
+
+/// A pile of identical items carried as a single entity, `quantity` deep
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Stackable {
+    pub quantity: i32,
+}
+
+/// Shatters when thrown, splashing everyone nearby with whatever it would do to a drinker
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Potion {}
+
+/// Made for throwing; anything else thrown hurts according to its weapon bonus, if any
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Throwable {
+    pub damage: i32,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Weight {
+    pub pounds: f32,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Attributes {
+    pub strength: i32,
+    pub intelligence: i32,
+}
+
+impl Attributes {
+    /// How much can be carried before it starts to slow you down, given any bonus
+    /// strength from equipment
+    pub fn carry_capacity(&self, strength_bonus: i32) -> f32 {
+        (self.strength + strength_bonus) as f32 * 10.0
+    }
+
+    pub fn max_mana(&self) -> i32 {
+        self.intelligence * 2
+    }
+
+    /// Sharper minds get their mana back sooner
+    pub fn mana_regen_turns(&self) -> i32 {
+        i32::max(2, 12 - self.intelligence / 2)
+    }
+
+    /// Extra damage (or healing) added to every spell cast
+    pub fn spell_power(&self) -> i32 {
+        i32::max(0, (self.intelligence - 8) / 2)
+    }
+
+    /// What a spell really costs to cast, once some of it has been thought away
+    pub fn mana_cost(&self, base_cost: i32) -> i32 {
+        i32::max(1, base_cost - (self.intelligence - 10) / 3)
+    }
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Mana {
+    pub current: i32,
+    pub max: i32,
+    pub recovery: i32,
+}
+
+/// A spell its owner has learned. Spells are cast through the same path as items, so the
+/// entity carries the same effect components a scroll would.
+#[derive(Component, Debug, ConvertSaveload, Clone)]
+pub struct KnownSpell {
+    pub owner: Entity,
+    pub mana_cost: i32,
+}
+
+/// Reading this teaches the named spell for good
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Spellbook {
+    pub spell: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum EncumbranceTier {
+    Unburdened,
+    Burdened,
+    Strained,
+}
+
+impl EncumbranceTier {
+    /// Lugging a heavy load around makes for clumsy fighting
+    pub fn melee_penalty(&self) -> i32 {
+        match self {
+            EncumbranceTier::Unburdened => 0,
+            EncumbranceTier::Burdened => 1,
+            EncumbranceTier::Strained => 2,
+        }
+    }
+}
+
+/// Weapons and armor wear down with use, and break when this runs out
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Durability {
+    pub current: i32,
+    pub max: i32,
+}
+
+impl Durability {
+    /// Badly damaged gear only gives half its usual bonus
+    pub fn effective_bonus(&self, bonus: i32) -> i32 {
+        if self.badly_damaged() {
+            bonus / 2
+        } else {
+            bonus
+        }
+    }
+
+    pub fn badly_damaged(&self) -> bool {
+        self.current * 4 <= self.max
+    }
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct RepairsItem {}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+    Epic,
+}
+
+impl Rarity {
+    /// Rarer items roll more (and stronger) enchantments
+    pub fn tier(&self) -> i32 {
+        match self {
+            Rarity::Common => 1,
+            Rarity::Uncommon => 2,
+            Rarity::Rare => 3,
+            Rarity::Epic => 4,
+        }
+    }
+
+    pub fn color(&self) -> RGB {
+        match self {
+            Rarity::Common => RGB::named(rltk::WHITE),
+            Rarity::Uncommon => RGB::named(rltk::GREEN),
+            Rarity::Rare => RGB::named(rltk::DODGER_BLUE),
+            Rarity::Epic => RGB::named(rltk::MAGENTA),
+        }
+    }
+}
+
+/// Marks generated equipment, along with how rare a find it is
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct MagicItem {
+    pub rarity: Rarity,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Element {
+    Fire,
+    Frost,
+    Shock,
+}
+
+impl Element {
+    /// How the log describes someone taking this kind of damage
+    pub fn verb(&self) -> &'static str {
+        match self {
+            Element::Fire => "burned",
+            Element::Frost => "frozen",
+            Element::Shock => "shocked",
+        }
+    }
+}
+
+/// Extra damage a weapon deals on every hit, regardless of armor
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct ElementalDamage {
+    pub element: Element,
+    pub damage: i32,
+}
+
+/// Raises the wearer's attributes while equipped
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct AttributeBonus {
+    pub strength: i32,
+}
+
+/// Nobody can pick up more than this multiple of their carrying capacity
+pub const MAX_LOAD_MULTIPLIER: f32 = 2.0;
+
+/// What an entity is carrying, kept up to date by the `EncumbranceSystem`
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Encumbrance {
+    pub carried: f32,
+    pub capacity: f32,
+    pub tier: EncumbranceTier,
+    pub lagging: bool,
+}
+
+impl Encumbrance {
+    pub fn current_tier(&self) -> EncumbranceTier {
+        if self.carried > self.capacity * 1.5 {
+            EncumbranceTier::Strained
+        } else if self.carried > self.capacity {
+            EncumbranceTier::Burdened
+        } else {
+            EncumbranceTier::Unburdened
+        }
+    }
+
+    pub fn can_lift(&self, pounds: f32) -> bool {
+        self.carried + pounds <= self.capacity * MAX_LOAD_MULTIPLIER
+    }
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Value {
+    pub base: i32,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Gold {
+    pub amount: i32,
+}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Purse {
+    pub gold: i32,
+}
+
+/// Holds items, as a chest or a monster's corpse does. Whatever is inside is `InBackpack`
+/// with the container as its owner; an `InflictsDamage` on the container is a trap that
+/// goes off the first time it's opened.
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Container {}
+
+#[derive(Component, Debug, Serialize, Deserialize, Clone)]
+pub struct Vendor {
+    pub price_percent: i32,
+}
+
+impl Vendor {
+    /// What the vendor charges for an item; everything costs more the deeper you go
+    pub fn buy_price(&self, value: &Value, depth: i32) -> i32 {
+        i32::max(1, value.base * self.price_percent * (10 + depth) / 1000)
+    }
+
+    /// What the vendor will pay for an item, which is a lot less than they'd sell it for
+    pub fn sell_price(&self, value: &Value, depth: i32) -> i32 {
+        i32::max(
+            1,
+            value.base * 40 * (10 + depth) / (self.price_percent * 10),
+        )
+    }
+}
+
 #[derive(Component, Debug, Serialize, Deserialize, Clone)]
 pub struct Hidden {}
 
@@ -207,4 +669,71 @@ pub struct SerializeMe;
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct SerializationHelper {
     pub map: super::map::Map,
+    pub uniques: UniqueTracker,
+    pub identification: ItemIdentification,
+}
+
+/// Resource remembering which unique monsters have been killed this run
+#[derive(Default, Serialize, Deserialize, Clone)]
+pub struct UniqueTracker {
+    pub killed: HashSet<String>,
+}
+
+/// Resource holding this run's made-up names for magic items, and which the player has learned
+#[derive(Default, Serialize, Deserialize, Clone)]
+pub struct ItemIdentification {
+    pub obfuscated: HashMap<String, String>,
+    pub identified: HashSet<String>,
+}
+
+impl ItemIdentification {
+    pub fn is_identified(&self, name: &str) -> bool {
+        !self.obfuscated.contains_key(name) || self.identified.contains(name)
+    }
+
+    /// The name the player knows an item by
+    pub fn display_name(&self, name: &str) -> String {
+        match self.obfuscated.get(name) {
+            Some(obfuscated) if !self.identified.contains(name) => obfuscated.to_string(),
+            _ => name.to_string(),
+        }
+    }
+
+    /// Returns true if this taught the player something new
+    pub fn identify(&mut self, name: &str) -> bool {
+        !self.is_identified(name) && self.identified.insert(name.to_string())
+    }
+}
+
+/// Kinds of item the player can choose to pick up just by walking over them
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum PickupCategory {
+    Gold,
+    Ammunition,
+}
+
+impl PickupCategory {
+    pub const ALL: [PickupCategory; 2] = [PickupCategory::Gold, PickupCategory::Ammunition];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PickupCategory::Gold => "Gold",
+            PickupCategory::Ammunition => "Ammunition (anything made for throwing)",
+        }
+    }
+}
+
+/// Resource holding the player's auto-pickup settings. These are preferences rather than
+/// game state, so they aren't saved and carry over from one run to the next.
+#[derive(Default)]
+pub struct AutoPickup {
+    pub enabled: HashSet<PickupCategory>,
+}
+
+impl AutoPickup {
+    pub fn toggle(&mut self, category: PickupCategory) {
+        if !self.enabled.remove(&category) {
+            self.enabled.insert(category);
+        }
+    }
 }
@@ -1,4 +1,8 @@
-use super::{gamelog::GameLog, CombatStats, Map, Name, Player, Position, RunState, SufferDamage};
+use super::{
+    gamelog::GameLog, monster_ai_system::SEARCH_TURNS, spawner, Ability, AiState, Behavior, Boss,
+    CombatStats, Equipped, InBackpack, Map, Name, PackMember, Player, Position, Routed, RunState,
+    SufferDamage, UniqueTracker,
+};
 use specs::prelude::*;
 
 pub struct DamageSystem;
@@ -10,10 +14,11 @@ impl<'a> System<'a> for DamageSystem {
         ReadStorage<'a, Position>,
         WriteExpect<'a, Map>,
         Entities<'a>,
+        WriteStorage<'a, Behavior>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut stats, mut damage, positions, mut map, entities) = data;
+        let (mut stats, mut damage, positions, mut map, entities, mut behaviors) = data;
 
         for (entity, mut stats, damage) in (&entities, &mut stats, &damage).join() {
             stats.hp -= damage.amount.iter().sum::<i32>();
@@ -21,6 +26,17 @@ impl<'a> System<'a> for DamageSystem {
             if let Some(pos) = pos {
                 let idx = map.xy_idx(pos.x, pos.y);
                 map.bloodstains.insert(idx);
+
+                // Nobody sleeps through being hurt, or carries on as if nothing happened
+                if let Some(behavior) = behaviors.get_mut(entity) {
+                    if behavior.state.is_unaware() {
+                        behavior.state = AiState::Searching {
+                            x: pos.x,
+                            y: pos.y,
+                            turns: SEARCH_TURNS,
+                        };
+                    }
+                }
             }
         }
 
@@ -57,6 +73,110 @@ pub fn delete_the_dead(ecs: &mut World) {
         }
     }
 
+    // Remember fallen uniques so they never turn up again
+    {
+        let bosses = ecs.read_storage::<Boss>();
+        let names = ecs.read_storage::<Name>();
+        let mut uniques = ecs.write_resource::<UniqueTracker>();
+        let mut log = ecs.write_resource::<GameLog>();
+        for victim in dead.iter() {
+            if let (Some(_boss), Some(name)) = (bosses.get(*victim), names.get(*victim)) {
+                uniques.killed.insert(name.name.to_string());
+                log.entries
+                    .push(format!("With {} slain, the way down opens.", name.name));
+            }
+        }
+    }
+
+    // Abilities die with their owners
+    {
+        let abilities = ecs.read_storage::<Ability>();
+        let entities = ecs.entities();
+        let orphaned: Vec<Entity> = (&entities, &abilities)
+            .join()
+            .filter(|(_entity, ability)| dead.contains(&ability.owner))
+            .map(|(entity, _ability)| entity)
+            .collect();
+        dead.extend(orphaned);
+    }
+
+    // Creatures leave a corpse behind, holding whatever they were carrying
+    let bodies: Vec<(Entity, i32, i32, String, i32)> = {
+        let combat_stats = ecs.read_storage::<CombatStats>();
+        let positions = ecs.read_storage::<Position>();
+        let names = ecs.read_storage::<Name>();
+        dead.iter()
+            .filter_map(|victim| {
+                match (
+                    combat_stats.get(*victim),
+                    positions.get(*victim),
+                    names.get(*victim),
+                ) {
+                    (Some(stats), Some(pos), Some(name)) => {
+                        Some((*victim, pos.x, pos.y, name.name.to_string(), stats.max_hp))
+                    }
+                    _ => None,
+                }
+            })
+            .collect()
+    };
+    let corpses: Vec<(Entity, Entity)> = bodies
+        .into_iter()
+        .map(|(victim, x, y, name, max_hp)| (victim, spawner::corpse(ecs, x, y, &name, max_hp)))
+        .collect();
+
+    let dropped: Vec<(Entity, Entity)> = {
+        let entities = ecs.entities();
+        let backpack = ecs.read_storage::<InBackpack>();
+        let equipped = ecs.read_storage::<Equipped>();
+        (&entities, (&backpack).maybe(), (&equipped).maybe())
+            .join()
+            .filter_map(|(item, pack, eq)| {
+                let owner = pack.map(|p| p.owner).or(eq.map(|e| e.owner));
+                owner
+                    .filter(|owner| dead.contains(owner))
+                    .map(|owner| (item, owner))
+            })
+            .collect()
+    };
+    for (item, owner) in dropped {
+        let container = match corpses.iter().find(|(victim, _)| *victim == owner) {
+            None => continue,
+            Some((_, corpse)) => *corpse,
+        };
+        ecs.write_storage::<Equipped>().remove(item);
+        ecs.write_storage::<InBackpack>()
+            .insert(item, InBackpack { owner: container })
+            .expect("Unable to insert backpack entry");
+    }
+
+    // A pack whose leader falls loses its nerve and scatters
+    {
+        let entities = ecs.entities();
+        let mut pack_members = ecs.write_storage::<PackMember>();
+        let mut routed = ecs.write_storage::<Routed>();
+        let mut behaviors = ecs.write_storage::<Behavior>();
+        let leaderless: Vec<Entity> = (&entities, &pack_members)
+            .join()
+            .filter(|(entity, member)| dead.contains(&member.leader) && !dead.contains(entity))
+            .map(|(entity, _member)| entity)
+            .collect();
+        if !leaderless.is_empty() {
+            let mut log = ecs.write_resource::<GameLog>();
+            log.entries
+                .push("The rest of the pack breaks and runs!".to_string());
+        }
+        for follower in leaderless {
+            pack_members.remove(follower);
+            routed
+                .insert(follower, Routed {})
+                .expect("Unable to insert marker");
+            if let Some(behavior) = behaviors.get_mut(follower) {
+                behavior.state = AiState::Fleeing;
+            }
+        }
+    }
+
     for victim in dead {
         ecs.delete_entity(victim).expect("Unable to delete");
     }
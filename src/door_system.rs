@@ -0,0 +1,140 @@
+use super::{
+    gamelog::GameLog,
+    noise_system::{NoiseBuilder, BASH_VOLUME, DOOR_VOLUME},
+    BashesDoors, BlocksTile, BlocksVisibility, Door, Map, Name, Position, Renderable, Viewshed,
+    WantsToBashDoor, WantsToCloseDoor, WantsToOpenDoor,
+};
+use specs::prelude::*;
+
+pub struct DoorSystem {}
+
+impl<'a> System<'a> for DoorSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, Map>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, rltk::RandomNumberGenerator>,
+        WriteStorage<'a, WantsToOpenDoor>,
+        WriteStorage<'a, WantsToCloseDoor>,
+        WriteStorage<'a, WantsToBashDoor>,
+        WriteStorage<'a, Door>,
+        WriteStorage<'a, BlocksTile>,
+        WriteStorage<'a, BlocksVisibility>,
+        WriteStorage<'a, Renderable>,
+        WriteStorage<'a, Viewshed>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, BashesDoors>,
+        WriteExpect<'a, NoiseBuilder>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            player_entity,
+            map,
+            mut log,
+            mut rng,
+            mut wants_open,
+            mut wants_close,
+            mut wants_bash,
+            mut doors,
+            mut blocks_movement,
+            mut blocks_visibility,
+            mut renderables,
+            mut viewsheds,
+            positions,
+            names,
+            bashers,
+            mut noise_builder,
+        ) = data;
+
+        let mut doors_changed = false;
+
+        for (_entity, open) in (&entities, &wants_open).join() {
+            if let Some(door) = doors.get_mut(open.door) {
+                if !door.open {
+                    door.open = true;
+                    blocks_visibility.remove(open.door);
+                    blocks_movement.remove(open.door);
+                    if let Some(render) = renderables.get_mut(open.door) {
+                        render.glyph = rltk::to_cp437('/');
+                    }
+                    if let Some(pos) = positions.get(open.door) {
+                        noise_builder.request(pos.x, pos.y, DOOR_VOLUME);
+                    }
+                    doors_changed = true;
+                }
+            }
+        }
+
+        for (entity, close) in (&entities, &wants_close).join() {
+            let in_the_way = positions.get(close.door).is_some_and(|pos| {
+                let idx = map.xy_idx(pos.x, pos.y);
+                map.tile_content[idx].iter().any(|e| *e != close.door)
+            });
+            if in_the_way {
+                if entity == *player_entity {
+                    log.entries
+                        .push("Something is in the way of the door.".to_string());
+                }
+                continue;
+            }
+
+            if let Some(door) = doors.get_mut(close.door) {
+                if door.open {
+                    door.open = false;
+                    blocks_visibility
+                        .insert(close.door, BlocksVisibility {})
+                        .expect("Unable to insert visibility blocker");
+                    blocks_movement
+                        .insert(close.door, BlocksTile {})
+                        .expect("Unable to insert movement blocker");
+                    if let Some(render) = renderables.get_mut(close.door) {
+                        render.glyph = rltk::to_cp437('+');
+                    }
+                    if let Some(pos) = positions.get(close.door) {
+                        noise_builder.request(pos.x, pos.y, DOOR_VOLUME);
+                    }
+                    if entity == *player_entity {
+                        log.entries.push("You close the door.".to_string());
+                    }
+                    doors_changed = true;
+                }
+            }
+        }
+
+        for (entity, bash, basher) in (&entities, &wants_bash, &bashers).join() {
+            if doors.get(bash.door).is_none() {
+                continue;
+            }
+            let name = names
+                .get(entity)
+                .map_or("Something".to_string(), |n| n.name.to_string());
+            if let Some(pos) = positions.get(bash.door) {
+                noise_builder.request(pos.x, pos.y, BASH_VOLUME);
+            }
+            if rng.roll_dice(1, 20) + basher.strength >= 15 {
+                log.entries
+                    .push(format!("{} smashes the door to splinters!", name));
+                entities.delete(bash.door).expect("Unable to delete door");
+                doors_changed = true;
+            } else {
+                log.entries.push(format!("{} slams into the door.", name));
+            }
+        }
+
+        // Everyone's view of the world may have changed
+        if doors_changed {
+            for viewshed in (&mut viewsheds).join() {
+                viewshed.dirty = true;
+            }
+        }
+
+        wants_open.clear();
+        wants_close.clear();
+        wants_bash.clear();
+    }
+}
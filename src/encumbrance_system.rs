@@ -0,0 +1,82 @@
+use super::{
+    gamelog::GameLog, inventory_system::stack_weight, AttributeBonus, Attributes, Encumbrance,
+    EncumbranceTier, Equipped, InBackpack, Stackable, Weight,
+};
+use specs::prelude::*;
+
+pub struct EncumbranceSystem {}
+
+impl<'a> System<'a> for EncumbranceSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        ReadStorage<'a, Attributes>,
+        WriteStorage<'a, Encumbrance>,
+        ReadStorage<'a, Weight>,
+        ReadStorage<'a, InBackpack>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, AttributeBonus>,
+        ReadStorage<'a, Stackable>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            player_entity,
+            mut log,
+            attributes,
+            mut encumbrance,
+            weights,
+            backpack,
+            equipped,
+            attribute_bonuses,
+            stacks,
+        ) = data;
+
+        for (entity, attributes, load) in (&entities, &attributes, &mut encumbrance).join() {
+            load.carried = (&weights, &backpack, stacks.maybe())
+                .join()
+                .filter(|(_weight, pack, _stack)| pack.owner == entity)
+                .map(|(weight, _pack, stack)| stack_weight(Some(weight), stack))
+                .sum::<f32>()
+                + (&weights, &equipped)
+                    .join()
+                    .filter(|(_weight, eq)| eq.owner == entity)
+                    .map(|(weight, _eq)| weight.pounds)
+                    .sum::<f32>();
+            let strength_bonus: i32 = (&attribute_bonuses, &equipped)
+                .join()
+                .filter(|(_bonus, eq)| eq.owner == entity)
+                .map(|(bonus, _eq)| bonus.strength)
+                .sum();
+            load.capacity = attributes.carry_capacity(strength_bonus);
+
+            let tier = load.current_tier();
+            if tier != load.tier && entity == *player_entity {
+                let message = match tier {
+                    EncumbranceTier::Unburdened => "Your load feels manageable again.",
+                    EncumbranceTier::Burdened => "You are burdened by your load.",
+                    EncumbranceTier::Strained => "You strain under the weight of your load!",
+                };
+                log.entries.push(message.to_string());
+            }
+            load.tier = tier;
+        }
+    }
+}
+
+/// A strained player loses every other turn to the monsters. Returns true if they should
+/// get another go before the player can act.
+pub fn player_falls_behind(ecs: &mut World) -> bool {
+    let player_entity = ecs.fetch::<Entity>();
+    let mut encumbrance = ecs.write_storage::<Encumbrance>();
+    match encumbrance.get_mut(*player_entity) {
+        Some(load) if load.tier == EncumbranceTier::Strained => {
+            load.lagging = !load.lagging;
+            load.lagging
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,34 @@
+#[derive(PartialEq, Clone, Copy)]
+pub enum Reaction {
+    Attack,
+    Ignore,
+    Flee,
+}
+
+/// How a member of `my_faction` reacts on seeing a member of `their_faction`
+pub fn faction_reaction(my_faction: &str, their_faction: &str) -> Reaction {
+    if my_faction == their_faction {
+        return Reaction::Ignore;
+    }
+
+    match my_faction {
+        "Player" => Reaction::Attack,
+        "Goblins" => match their_faction {
+            "Player" | "Orcs" => Reaction::Attack,
+            _ => Reaction::Ignore,
+        },
+        "Orcs" => match their_faction {
+            "Player" | "Goblins" => Reaction::Attack,
+            _ => Reaction::Ignore,
+        },
+        "Predators" => match their_faction {
+            "Player" | "Wildlife" => Reaction::Attack,
+            _ => Reaction::Ignore,
+        },
+        "Wildlife" => match their_faction {
+            "Player" | "Predators" => Reaction::Flee,
+            _ => Reaction::Ignore,
+        },
+        _ => Reaction::Ignore,
+    }
+}
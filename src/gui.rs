@@ -1,8 +1,11 @@
-use crate::{rex_assets::RexAssets, Hidden};
+use crate::{rex_assets::RexAssets, Hidden, MAX_NUTRITION};
 
 use super::{
-    camera, gamelog::GameLog, CombatStats, Equipped, HungerClock, HungerState, InBackpack, Map,
-    Name, Player, Position, RunState, State, Viewshed,
+    camera, gamelog::GameLog, AiState, Attributes, AutoPickup, Behavior, Charges, CombatStats,
+    CurseKnown, Cursed, Durability, Encumbrance, EncumbranceTier, Equippable, Equipped, Fuel,
+    HungerClock, HungerState, InBackpack, Item, ItemIdentification, KnownSpell, MagicItem, Mana,
+    Map, Name, Perishable, PickupCategory, Player, Position, Purse, RunState, Sickness, Stackable,
+    State, Value, Vendor, Viewshed,
 };
 use rltk::{Point, Rltk, VirtualKeyCode, RGB};
 use specs::prelude::*;
@@ -53,30 +56,63 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
             RGB::named(rltk::BLACK),
         );
 
-        match hc.state {
-            HungerState::WellFed => ctx.print_color(
-                71,
-                42,
-                RGB::named(rltk::GREEN),
-                RGB::named(rltk::BLACK),
-                "Well Fed",
-            ),
-            HungerState::Normal => (),
-            HungerState::Hungry => ctx.print_color(
-                71,
-                42,
-                RGB::named(rltk::ORANGE),
-                RGB::named(rltk::BLACK),
-                "Hungry",
-            ),
-            HungerState::Starving => ctx.print_color(
-                71,
-                42,
-                RGB::named(rltk::RED),
-                RGB::named(rltk::BLACK),
-                "Starving",
-            ),
-        }
+        let (hunger_label, hunger_color) = match hc.state {
+            HungerState::WellFed => ("Well Fed", RGB::named(rltk::GREEN)),
+            HungerState::Normal => ("Fed", RGB::named(rltk::WHEAT)),
+            HungerState::Hungry => ("Hungry", RGB::named(rltk::ORANGE)),
+            HungerState::Starving => ("Starving", RGB::named(rltk::RED)),
+        };
+        ctx.print_color(52, 42, hunger_color, RGB::named(rltk::BLACK), hunger_label);
+        ctx.draw_bar_horizontal(
+            61,
+            42,
+            18,
+            hc.nutrition,
+            MAX_NUTRITION,
+            hunger_color,
+            RGB::named(rltk::BLACK),
+        );
+    }
+
+    let player_entity = ecs.fetch::<Entity>();
+    if let Some(mana) = ecs.read_storage::<Mana>().get(*player_entity) {
+        let mana_text = format!(" MP: {} / {} ", mana.current, mana.max);
+        ctx.print_color(
+            12,
+            42,
+            RGB::named(rltk::CYAN),
+            RGB::named(rltk::BLACK),
+            &mana_text,
+        );
+        ctx.draw_bar_horizontal(
+            28,
+            42,
+            16,
+            mana.current,
+            mana.max,
+            RGB::named(rltk::BLUE),
+            RGB::named(rltk::BLACK),
+        );
+    }
+    if ecs.read_storage::<Sickness>().get(*player_entity).is_some() {
+        ctx.print_color(
+            46,
+            42,
+            RGB::named(rltk::CHARTREUSE),
+            RGB::named(rltk::BLACK),
+            "Sick",
+        );
+    }
+
+    let purses = ecs.read_storage::<Purse>();
+    for (_player, purse) in (&players, &purses).join() {
+        ctx.print_color(
+            71,
+            41,
+            RGB::named(rltk::GOLD),
+            RGB::named(rltk::BLACK),
+            format!("Gold: {}", purse.gold),
+        );
     }
 
     let log = ecs.fetch::<GameLog>();
@@ -115,6 +151,9 @@ fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
     let names = ecs.read_storage::<Name>();
     let positions = ecs.read_storage::<Position>();
     let hidden = ecs.read_storage::<Hidden>();
+    let behaviors = ecs.read_storage::<Behavior>();
+    let identification = ecs.fetch::<ItemIdentification>();
+    let entities = ecs.entities();
 
     let mouse_pos = ctx.mouse_pos();
     let mut mouse_map_pos = mouse_pos;
@@ -131,9 +170,16 @@ fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
         return;
     }
     let mut tooltip: Vec<String> = Vec::new();
-    for (name, position, _hidden) in (&names, &positions, !&hidden).join() {
+    for (entity, name, position, _hidden) in (&entities, &names, &positions, !&hidden).join() {
         if position.x == mouse_map_pos.0 && position.y == mouse_map_pos.1 {
-            tooltip.push(name.name.to_string());
+            match behaviors.get(entity) {
+                None => tooltip.push(item_label(ecs, &identification, entity, &name.name)),
+                Some(behavior) => tooltip.push(format!(
+                    "{} ({})",
+                    name.name,
+                    ai_state_description(behavior.state)
+                )),
+            }
         }
     }
 
@@ -212,6 +258,20 @@ fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
     }
 }
 
+fn ai_state_description(state: AiState) -> &'static str {
+    match state {
+        AiState::Sleeping => "asleep",
+        AiState::Idle => "unaware",
+        AiState::Wandering { .. } => "wandering, unaware",
+        AiState::Chasing { .. } => "hunting",
+        AiState::Searching { .. } => "searching",
+        AiState::Fleeing => "fleeing",
+    }
+}
+
+/// Menus longer than this are split into pages, as there's only one letter per entry
+const MENU_PAGE_SIZE: usize = 26;
+
 #[derive(PartialEq, Copy, Clone)]
 pub enum ItemMenuResult {
     Cancel,
@@ -219,70 +279,600 @@ pub enum ItemMenuResult {
     Selected,
 }
 
+/// Whether the player has found out that an item is cursed
+fn known_cursed(ecs: &World, item: Entity) -> bool {
+    ecs.read_storage::<Cursed>().get(item).is_some()
+        && ecs.read_storage::<CurseKnown>().get(item).is_some()
+}
+
+/// How an item appears to the player: its known name, charges and curse
+fn item_label(
+    ecs: &World,
+    identification: &ItemIdentification,
+    item: Entity,
+    name: &str,
+) -> String {
+    let mut label = identification.display_name(name);
+    if let Some(charges) = ecs.read_storage::<Charges>().get(item) {
+        label += &format!(" ({}/{})", charges.current, charges.max);
+    }
+    if let Some(durability) = ecs.read_storage::<Durability>().get(item) {
+        if durability.badly_damaged() {
+            label += " (badly damaged)";
+        } else if durability.current * 2 <= durability.max {
+            label += " (worn)";
+        }
+    }
+    if known_cursed(ecs, item) {
+        label += " (cursed)";
+    }
+    if ecs
+        .read_storage::<Perishable>()
+        .get(item)
+        .is_some_and(|food| food.rotten())
+    {
+        label += " (rotten)";
+    }
+    if let Some(fuel) = ecs.read_storage::<Fuel>().get(item) {
+        if fuel.turns > 0 {
+            label += &format!(" ({} turns)", fuel.turns);
+        } else {
+            label += " (burnt out)";
+        }
+    }
+    label
+}
+
+/// The player's carried (or equipped) items, along with their real names
+fn player_items(ecs: &World, equip: bool) -> Vec<(Entity, String)> {
+    let player_entity = ecs.fetch::<Entity>();
+    let names = ecs.read_storage::<Name>();
+    let backpack = ecs.read_storage::<InBackpack>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let entities = ecs.entities();
+
+    if equip {
+        (&entities, &equipped, &names)
+            .join()
+            .filter(|item| item.1.owner == *player_entity)
+            .map(|item| (item.0, item.2.name.to_string()))
+            .collect()
+    } else {
+        (&entities, &backpack, &names)
+            .join()
+            .filter(|item| item.1.owner == *player_entity)
+            .map(|item| (item.0, item.2.name.to_string()))
+            .collect()
+    }
+}
+
+/// Pairs each item in a menu with how many there are in its stack
+fn with_quantities(ecs: &World, items: Vec<(Entity, String)>) -> Vec<(Entity, String, i32)> {
+    let stacks = ecs.read_storage::<Stackable>();
+    items
+        .into_iter()
+        .map(|(entity, name)| {
+            let quantity = stacks.get(entity).map_or(1, |stack| stack.quantity);
+            (entity, name, quantity)
+        })
+        .collect()
+}
+
+/// Works out which page of a long menu is showing, returning it and the number of pages
+fn menu_page(gs: &State, entries: usize) -> (usize, usize) {
+    let pages = usize::max(1, entries.div_ceil(MENU_PAGE_SIZE));
+    (usize::min(gs.menu_page, pages - 1), pages)
+}
+
+/// Handles the page-turning keys, returning true if the key was one of them
+fn turn_page(gs: &mut State, key: VirtualKeyCode, page: usize, pages: usize) -> bool {
+    match key {
+        VirtualKeyCode::Left | VirtualKeyCode::PageUp => {
+            gs.menu_page = page.saturating_sub(1);
+            true
+        }
+        VirtualKeyCode::Right | VirtualKeyCode::PageDown => {
+            gs.menu_page = usize::min(page + 1, pages - 1);
+            true
+        }
+        _ => false,
+    }
+}
+
 fn draw_inventory(
     gs: &mut State,
     ctx: &mut Rltk,
     text: String,
-    equip: bool,
+    source: Vec<(Entity, String)>,
 ) -> (ItemMenuResult, Option<Entity>) {
-    let player_entity = gs.ecs.fetch::<Entity>();
-    let names = gs.ecs.read_storage::<Name>();
-    let backpack = gs.ecs.read_storage::<InBackpack>();
-    let equipped = gs.ecs.read_storage::<Equipped>();
-    let entities = gs.ecs.entities();
+    let stacks = with_quantities(&gs.ecs, source);
+    let (page, pages) = menu_page(gs, stacks.len());
+    let shown =
+        &stacks[page * MENU_PAGE_SIZE..usize::min(stacks.len(), (page + 1) * MENU_PAGE_SIZE)];
+    let count = shown.len();
 
-    let count = if equip {
-        (&equipped, &names)
-            .join()
-            .filter(|item| item.0.owner == *player_entity)
-            .count()
+    let top = (25 - (count / 2)) as i32;
+    ctx.draw_box(
+        15,
+        top - 2,
+        41,
+        count as i32 + 3,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+    );
+    ctx.print_color(
+        18,
+        top - 2,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        text,
+    );
+    {
+        let player_entity = gs.ecs.fetch::<Entity>();
+        if let Some(load) = gs.ecs.read_storage::<Encumbrance>().get(*player_entity) {
+            let weight = format!("{:.1}/{:.0} lbs", load.carried, load.capacity);
+            let fg = match load.tier {
+                EncumbranceTier::Unburdened => RGB::named(rltk::YELLOW),
+                EncumbranceTier::Burdened => RGB::named(rltk::ORANGE),
+                EncumbranceTier::Strained => RGB::named(rltk::RED),
+            };
+            ctx.print_color(
+                54 - weight.len() as i32,
+                top - 2,
+                fg,
+                RGB::named(rltk::BLACK),
+                weight,
+            );
+        }
+    }
+    let footer = if pages > 1 {
+        format!("ESCAPE to cancel, page {}/{} (arrows)", page + 1, pages)
     } else {
-        (&backpack, &names)
+        "ESCAPE to cancel".to_string()
+    };
+    ctx.print_color(
+        18,
+        top + count as i32 + 1,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        footer,
+    );
+
+    {
+        let identification = gs.ecs.fetch::<ItemIdentification>();
+        for (j, (entity, name, quantity)) in shown.iter().enumerate() {
+            let y = top + j as i32;
+            ctx.set(
+                17,
+                y,
+                RGB::named(rltk::WHITE),
+                RGB::named(rltk::BLACK),
+                rltk::to_cp437('('),
+            );
+            ctx.set(
+                18,
+                y,
+                RGB::named(rltk::YELLOW),
+                RGB::named(rltk::BLACK),
+                97 + j as rltk::FontCharType,
+            );
+            ctx.set(
+                19,
+                y,
+                RGB::named(rltk::WHITE),
+                RGB::named(rltk::BLACK),
+                rltk::to_cp437(')'),
+            );
+
+            let fg = if known_cursed(&gs.ecs, *entity) {
+                RGB::named(rltk::RED)
+            } else {
+                gs.ecs
+                    .read_storage::<MagicItem>()
+                    .get(*entity)
+                    .map_or(RGB::named(rltk::WHITE), |magic| magic.rarity.color())
+            };
+            let mut label = item_label(&gs.ecs, &identification, *entity, name);
+            if *quantity > 1 {
+                label += &format!(" (x{})", quantity);
+            }
+            ctx.print_color(21, y, fg, RGB::named(rltk::BLACK), label);
+        }
+    }
+
+    match ctx.key {
+        None => (ItemMenuResult::NoResponse, None),
+        Some(key) => match key {
+            VirtualKeyCode::Escape => {
+                gs.menu_page = 0;
+                (ItemMenuResult::Cancel, None)
+            }
+            _ if turn_page(gs, key, page, pages) => (ItemMenuResult::NoResponse, None),
+            _ => {
+                let selection = rltk::letter_to_option(key);
+                if selection > -1 && selection < count as i32 {
+                    gs.menu_page = 0;
+                    return (ItemMenuResult::Selected, Some(shown[selection as usize].0));
+                }
+                (ItemMenuResult::NoResponse, None)
+            }
+        },
+    }
+}
+
+pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
+    let items = player_items(&gs.ecs, false);
+    draw_inventory(gs, ctx, "Inventory".to_string(), items)
+}
+
+pub fn drop_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
+    let items = player_items(&gs.ecs, false);
+    draw_inventory(gs, ctx, "Drop Which Item?".to_string(), items)
+}
+
+pub fn remove_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
+    let items = player_items(&gs.ecs, true);
+    draw_inventory(gs, ctx, "Remove Which Item?".to_string(), items)
+}
+
+pub fn throw_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
+    let items = player_items(&gs.ecs, false);
+    draw_inventory(gs, ctx, "Throw Which Item?".to_string(), items)
+}
+
+/// Lists only the carried items whose true nature is still unknown: unidentified item
+/// types, and equipment that might be cursed
+pub fn identify_item_menu(
+    gs: &mut State,
+    ctx: &mut Rltk,
+    scroll: Entity,
+) -> (ItemMenuResult, Option<Entity>) {
+    let mut items = player_items(&gs.ecs, false);
+    {
+        let identification = gs.ecs.fetch::<ItemIdentification>();
+        let equippable = gs.ecs.read_storage::<Equippable>();
+        let curse_known = gs.ecs.read_storage::<CurseKnown>();
+        items.retain(|(entity, name)| {
+            let unknown_curse =
+                equippable.get(*entity).is_some() && curse_known.get(*entity).is_none();
+            *entity != scroll && (!identification.is_identified(name) || unknown_curse)
+        });
+    }
+    if items.is_empty() {
+        let mut gamelog = gs.ecs.fetch_mut::<GameLog>();
+        gamelog
+            .entries
+            .push("You have nothing that needs identifying.".to_string());
+        return (ItemMenuResult::Cancel, None);
+    }
+    draw_inventory(gs, ctx, "Identify Which Item?".to_string(), items)
+}
+
+/// Lists the spells the player knows, along with what each costs them to cast
+pub fn cast_spell_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
+    let spells: Vec<(Entity, String)> = {
+        let player_entity = gs.ecs.fetch::<Entity>();
+        let names = gs.ecs.read_storage::<Name>();
+        let known_spells = gs.ecs.read_storage::<KnownSpell>();
+        let attributes = gs.ecs.read_storage::<Attributes>();
+        let entities = gs.ecs.entities();
+        (&entities, &known_spells, &names)
             .join()
-            .filter(|item| item.0.owner == *player_entity)
-            .count()
+            .filter(|(_spell, known, _name)| known.owner == *player_entity)
+            .map(|(spell, known, name)| {
+                let cost = attributes
+                    .get(*player_entity)
+                    .map_or(known.mana_cost, |a| a.mana_cost(known.mana_cost));
+                (spell, format!("{} ({} mana)", name.name, cost))
+            })
+            .collect()
     };
+    if spells.is_empty() {
+        let mut gamelog = gs.ecs.fetch_mut::<GameLog>();
+        gamelog
+            .entries
+            .push("You don't know any spells.".to_string());
+        return (ItemMenuResult::Cancel, None);
+    }
+    draw_inventory(gs, ctx, "Cast Which Spell?".to_string(), spells)
+}
+
+/// Lists everything the player carries or wears that has seen some wear
+pub fn repair_item_menu(
+    gs: &mut State,
+    ctx: &mut Rltk,
+    kit: Entity,
+) -> (ItemMenuResult, Option<Entity>) {
+    let mut items = player_items(&gs.ecs, true);
+    items.append(&mut player_items(&gs.ecs, false));
+    {
+        let durability = gs.ecs.read_storage::<Durability>();
+        items.retain(|(entity, _name)| {
+            *entity != kit && durability.get(*entity).is_some_and(|d| d.current < d.max)
+        });
+    }
+    if items.is_empty() {
+        let mut gamelog = gs.ecs.fetch_mut::<GameLog>();
+        gamelog
+            .entries
+            .push("You have nothing that needs repairing.".to_string());
+        return (ItemMenuResult::Cancel, None);
+    }
+    draw_inventory(gs, ctx, "Repair Which Item?".to_string(), items)
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum LootResult {
+    NoResponse,
+    Cancel,
+    TakeOne,
+    TakeAll,
+}
+
+/// Lists what's inside a container; a letter takes that item, and space takes the lot
+pub fn show_container(
+    gs: &mut State,
+    ctx: &mut Rltk,
+    container: Entity,
+) -> (LootResult, Option<Entity>) {
+    let (title, contents) = {
+        let names = gs.ecs.read_storage::<Name>();
+        let backpack = gs.ecs.read_storage::<InBackpack>();
+        let entities = gs.ecs.entities();
+        let title = names
+            .get(container)
+            .map_or("Container".to_string(), |n| n.name.to_string());
+        let contents: Vec<(Entity, String)> = (&entities, &backpack, &names)
+            .join()
+            .filter(|item| item.1.owner == container)
+            .map(|item| (item.0, item.2.name.to_string()))
+            .collect();
+        (title, contents)
+    };
+    if contents.is_empty() {
+        gs.menu_page = 0;
+        return (LootResult::Cancel, None);
+    }
+    loot_menu(gs, ctx, title, contents)
+}
+
+/// Lists the items on the player's tile; a letter picks that one up, and space takes the lot
+pub fn pickup_item_menu(gs: &mut State, ctx: &mut Rltk) -> (LootResult, Option<Entity>) {
+    let items: Vec<(Entity, String)> = {
+        let player_pos = gs.ecs.fetch::<Point>();
+        let names = gs.ecs.read_storage::<Name>();
+        let positions = gs.ecs.read_storage::<Position>();
+        let items = gs.ecs.read_storage::<Item>();
+        let entities = gs.ecs.entities();
+        (&entities, &items, &positions, &names)
+            .join()
+            .filter(|item| item.2.x == player_pos.x && item.2.y == player_pos.y)
+            .map(|item| (item.0, item.3.name.to_string()))
+            .collect()
+    };
+    if items.is_empty() {
+        gs.menu_page = 0;
+        return (LootResult::Cancel, None);
+    }
+    loot_menu(gs, ctx, "Pick Up Which Item?".to_string(), items)
+}
 
-    let mut y = (25 - (count / 2)) as i32;
+fn loot_menu(
+    gs: &mut State,
+    ctx: &mut Rltk,
+    title: String,
+    items: Vec<(Entity, String)>,
+) -> (LootResult, Option<Entity>) {
+    match draw_inventory(gs, ctx, title, items) {
+        (ItemMenuResult::Cancel, _) => (LootResult::Cancel, None),
+        (ItemMenuResult::Selected, item) => (LootResult::TakeOne, item),
+        (ItemMenuResult::NoResponse, _) => match ctx.key {
+            Some(VirtualKeyCode::Space) => {
+                gs.menu_page = 0;
+                (LootResult::TakeAll, None)
+            }
+            _ => (LootResult::NoResponse, None),
+        },
+    }
+}
+
+/// Lets the player choose which kinds of item they pick up just by walking over them
+pub fn auto_pickup_menu(
+    gs: &mut State,
+    ctx: &mut Rltk,
+) -> (ItemMenuResult, Option<PickupCategory>) {
+    let count = PickupCategory::ALL.len();
+    let top = (25 - (count / 2)) as i32;
     ctx.draw_box(
         15,
-        y - 2,
-        31,
+        top - 2,
+        51,
         count as i32 + 3,
         RGB::named(rltk::WHITE),
         RGB::named(rltk::BLACK),
     );
     ctx.print_color(
         18,
-        y - 2,
+        top - 2,
         RGB::named(rltk::YELLOW),
         RGB::named(rltk::BLACK),
-        text,
+        "Auto-Pickup (letter to toggle)",
     );
     ctx.print_color(
         18,
-        y + count as i32 + 1,
+        top + count as i32 + 1,
         RGB::named(rltk::YELLOW),
         RGB::named(rltk::BLACK),
-        "ESCAPE to cancel",
+        "ESCAPE when done",
     );
 
-    let mut equippable: Vec<Entity> = Vec::new();
-    let mut j = 0;
-    let source: Vec<(Entity, &Name)> = if equip {
-        (&entities, &equipped, &names)
+    {
+        let settings = gs.ecs.fetch::<AutoPickup>();
+        for (j, category) in PickupCategory::ALL.iter().enumerate() {
+            let y = top + j as i32;
+            ctx.set(
+                17,
+                y,
+                RGB::named(rltk::WHITE),
+                RGB::named(rltk::BLACK),
+                rltk::to_cp437('('),
+            );
+            ctx.set(
+                18,
+                y,
+                RGB::named(rltk::YELLOW),
+                RGB::named(rltk::BLACK),
+                97 + j as rltk::FontCharType,
+            );
+            ctx.set(
+                19,
+                y,
+                RGB::named(rltk::WHITE),
+                RGB::named(rltk::BLACK),
+                rltk::to_cp437(')'),
+            );
+
+            let (fg, state) = if settings.enabled.contains(category) {
+                (RGB::named(rltk::GREEN), "[on] ")
+            } else {
+                (RGB::named(rltk::GREY), "[off]")
+            };
+            ctx.print_color(21, y, fg, RGB::named(rltk::BLACK), state);
+            ctx.print(27, y, category.label());
+        }
+    }
+
+    match ctx.key {
+        None => (ItemMenuResult::NoResponse, None),
+        Some(key) => match key {
+            VirtualKeyCode::Escape => (ItemMenuResult::Cancel, None),
+            _ => {
+                let selection = rltk::letter_to_option(key);
+                if selection > -1 && selection < count as i32 {
+                    return (
+                        ItemMenuResult::Selected,
+                        Some(PickupCategory::ALL[selection as usize]),
+                    );
+                }
+                (ItemMenuResult::NoResponse, None)
+            }
+        },
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum VendorMode {
+    Buy,
+    Sell,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum VendorResult {
+    NoResponse,
+    Cancel,
+    Buy,
+    Sell,
+    BuyMode,
+    SellMode,
+}
+
+pub fn show_vendor(
+    gs: &mut State,
+    ctx: &mut Rltk,
+    vendor: Entity,
+    mode: VendorMode,
+) -> (VendorResult, Option<Entity>) {
+    let player_entity = *gs.ecs.fetch::<Entity>();
+    let depth = gs.ecs.fetch::<Map>().depth;
+
+    let vendor_info = match gs.ecs.read_storage::<Vendor>().get(vendor) {
+        None => return (VendorResult::Cancel, None),
+        Some(vendor_info) => vendor_info.clone(),
+    };
+
+    // The vendor shows their stock when buying, and what you have of value when selling
+    let owner = match mode {
+        VendorMode::Buy => vendor,
+        VendorMode::Sell => player_entity,
+    };
+    let goods: Vec<(Entity, String, i32)> = {
+        let names = gs.ecs.read_storage::<Name>();
+        let backpack = gs.ecs.read_storage::<InBackpack>();
+        let values = gs.ecs.read_storage::<Value>();
+        let identification = gs.ecs.fetch::<ItemIdentification>();
+        let entities = gs.ecs.entities();
+
+        let for_sale: Vec<(Entity, String)> = (&entities, &backpack, &names, &values)
             .join()
-            .filter(|item| item.1.owner == *player_entity)
-            .map(|item| (item.0, item.2))
+            .filter(|item| item.1.owner == owner)
+            .map(|item| (item.0, identification.display_name(&item.2.name)))
+            .collect();
+        with_quantities(&gs.ecs, for_sale)
+            .into_iter()
+            .map(|(entity, name, quantity)| {
+                let value = values.get(entity).unwrap();
+                let price = match mode {
+                    VendorMode::Buy => vendor_info.buy_price(value, depth),
+                    VendorMode::Sell => vendor_info.sell_price(value, depth),
+                };
+                let label = if quantity > 1 {
+                    format!("{} (x{})", name, quantity)
+                } else {
+                    name
+                };
+                (entity, label, price)
+            })
             .collect()
+    };
+    let (page, pages) = menu_page(gs, goods.len());
+    let shown = &goods[page * MENU_PAGE_SIZE..usize::min(goods.len(), (page + 1) * MENU_PAGE_SIZE)];
+    let count = shown.len();
+
+    let top = (25 - (count / 2)) as i32;
+    ctx.draw_box(
+        15,
+        top - 2,
+        51,
+        count as i32 + 3,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+    );
+    let title = match mode {
+        VendorMode::Buy => "Buy Which Item? (space to switch to selling)",
+        VendorMode::Sell => "Sell Which Item? (space to switch to buying)",
+    };
+    ctx.print_color(
+        18,
+        top - 2,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        title,
+    );
+    let gold = gs
+        .ecs
+        .read_storage::<Purse>()
+        .get(player_entity)
+        .map_or(0, |purse| purse.gold);
+    let footer = if pages > 1 {
+        format!(
+            "You have {} gold. ESCAPE to cancel, page {}/{}",
+            gold,
+            page + 1,
+            pages
+        )
     } else {
-        (&entities, &backpack, &names)
-            .join()
-            .filter(|item| item.1.owner == *player_entity)
-            .map(|item| (item.0, item.2))
-            .collect()
+        format!("You have {} gold. ESCAPE to cancel", gold)
     };
-    for (entity, name) in source {
+    ctx.print_color(
+        18,
+        top + count as i32 + 1,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        footer,
+    );
+
+    for (j, (_entity, name, price)) in shown.iter().enumerate() {
+        let y = top + j as i32;
         ctx.set(
             17,
             y,
@@ -305,42 +895,43 @@ fn draw_inventory(
             rltk::to_cp437(')'),
         );
 
-        ctx.print(21, y, &name.name.to_string());
-        equippable.push(entity);
-        y += 1;
-        j += 1;
+        ctx.print(21, y, name);
+        ctx.print_color(
+            56,
+            y,
+            RGB::named(rltk::GOLD),
+            RGB::named(rltk::BLACK),
+            format!("{} gold", price),
+        );
     }
 
     match ctx.key {
-        None => (ItemMenuResult::NoResponse, None),
+        None => (VendorResult::NoResponse, None),
         Some(key) => match key {
-            VirtualKeyCode::Escape => (ItemMenuResult::Cancel, None),
+            VirtualKeyCode::Escape => {
+                gs.menu_page = 0;
+                (VendorResult::Cancel, None)
+            }
+            VirtualKeyCode::Space => match mode {
+                VendorMode::Buy => (VendorResult::SellMode, None),
+                VendorMode::Sell => (VendorResult::BuyMode, None),
+            },
+            _ if turn_page(gs, key, page, pages) => (VendorResult::NoResponse, None),
             _ => {
                 let selection = rltk::letter_to_option(key);
                 if selection > -1 && selection < count as i32 {
-                    return (
-                        ItemMenuResult::Selected,
-                        Some(equippable[selection as usize]),
-                    );
+                    let result = match mode {
+                        VendorMode::Buy => VendorResult::Buy,
+                        VendorMode::Sell => VendorResult::Sell,
+                    };
+                    return (result, Some(shown[selection as usize].0));
                 }
-                (ItemMenuResult::NoResponse, None)
+                (VendorResult::NoResponse, None)
             }
         },
     }
 }
 
-pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
-    draw_inventory(gs, ctx, "Inventory".to_string(), false)
-}
-
-pub fn drop_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
-    draw_inventory(gs, ctx, "Drop Which Item?".to_string(), false)
-}
-
-pub fn remove_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
-    draw_inventory(gs, ctx, "Remove Which Item?".to_string(), true)
-}
-
 pub fn ranged_target(
     gs: &mut State,
     ctx: &mut Rltk,
@@ -1,6 +1,9 @@
-use super::{gamelog::GameLog, HungerClock, HungerState, RunState, SufferDamage};
+use super::{gamelog::GameLog, HungerClock, HungerState, RunState, Sickness, SufferDamage};
 use specs::prelude::*;
 
+/// How many times faster hunger drains while sick
+const SICK_HUNGER: i32 = 3;
+
 pub struct HungerSystem {}
 
 impl<'a> System<'a> for HungerSystem {
@@ -12,12 +15,21 @@ impl<'a> System<'a> for HungerSystem {
         ReadExpect<'a, RunState>,
         WriteStorage<'a, SufferDamage>,
         WriteExpect<'a, GameLog>,
+        WriteStorage<'a, Sickness>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (entities, mut hunger_clock, player_entity, runstate, mut inflict_damage, mut log) =
-            data;
+        let (
+            entities,
+            mut hunger_clock,
+            player_entity,
+            runstate,
+            mut inflict_damage,
+            mut log,
+            mut sickness,
+        ) = data;
 
+        let mut recovered: Vec<Entity> = Vec::new();
         for (entity, mut clock) in (&entities, &mut hunger_clock).join() {
             let mut proceed = false;
 
@@ -36,43 +48,47 @@ impl<'a> System<'a> for HungerSystem {
             }
 
             if proceed {
-                clock.duration -= 1;
-                if clock.duration < 1 {
-                    match clock.state {
-                        HungerState::WellFed => {
-                            clock.state = HungerState::Normal;
-                            clock.duration = 200;
-                            if entity == *player_entity {
-                                log.entries.push("You are no longer well fed.".to_string());
-                            }
+                let mut hunger = 1;
+                if let Some(sick) = sickness.get_mut(entity) {
+                    hunger = SICK_HUNGER;
+                    sick.turns -= 1;
+                    if sick.turns < 1 {
+                        recovered.push(entity);
+                        if entity == *player_entity {
+                            log.entries.push("You feel better.".to_string());
                         }
+                    }
+                }
+                clock.nutrition = i32::max(0, clock.nutrition - hunger);
+
+                let state = HungerState::from_nutrition(clock.nutrition);
+                if state != clock.state && entity == *player_entity {
+                    match state {
+                        HungerState::WellFed => (),
                         HungerState::Normal => {
-                            clock.state = HungerState::Hungry;
-                            clock.duration = 200;
-                            if entity == *player_entity {
-                                log.entries.push("You are hungry.".to_string());
-                            }
-                        }
-                        HungerState::Hungry => {
-                            clock.state = HungerState::Starving;
-                            clock.duration = 200;
-                            if entity == *player_entity {
-                                log.entries.push("You are starving!".to_string());
-                            }
-                        }
-                        HungerState::Starving => {
-                            // Inflict damage from hunger
-                            if entity == *player_entity {
-                                log.entries.push(
-                                    "Your hunger pangs are getting painful! You suffer 1 hp damage."
-                                        .to_string(),
-                                );
-                                SufferDamage::new_damage(&mut inflict_damage, entity, 1);
-                            }
+                            log.entries.push("You are no longer well fed.".to_string())
                         }
+                        HungerState::Hungry => log.entries.push("You are hungry.".to_string()),
+                        HungerState::Starving => log.entries.push("You are starving!".to_string()),
+                    }
+                }
+                clock.state = state;
+
+                if clock.state == HungerState::Starving {
+                    // Inflict damage from hunger
+                    if entity == *player_entity {
+                        log.entries.push(
+                            "Your hunger pangs are getting painful! You suffer 1 hp damage."
+                                .to_string(),
+                        );
+                        SufferDamage::new_damage(&mut inflict_damage, entity, 1);
                     }
                 }
             }
         }
+
+        for entity in recovered {
+            sickness.remove(entity);
+        }
     }
 }
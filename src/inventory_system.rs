@@ -1,12 +1,105 @@
-use crate::{particle_system::ParticleBuilder, AreaOfEffect, Confusion, HungerState};
+use crate::{
+    particle_system::ParticleBuilder, AreaOfEffect, Confusion, HungerState, Perishable, Sickness,
+    MAX_NUTRITION,
+};
 
 use super::{
-    gamelog::GameLog, CombatStats, Consumable, Equippable, Equipped, HungerClock, InBackpack,
-    InflictsDamage, MagicMapper, Map, Name, Position, ProvidesFood, ProvidesHealing, RunState,
-    SufferDamage, WantsToDropItem, WantsToPickupItem, WantsToRemoveItem, WantsToUseItem,
+    gamelog::GameLog, Attributes, Charges, CombatStats, Consumable, Container, CurseKnown, Cursed,
+    Encumbrance, Equippable, Equipped, Gold, HungerClock, InBackpack, InflictsDamage,
+    ItemIdentification, KnownSpell, MagicMapper, Mana, Map, Name, Position, ProvidesFood,
+    ProvidesHealing, Purse, Recharges, RemovesCurse, RunState, Stackable, SufferDamage,
+    TeleportKind, Teleports, WantsToDropItem, WantsToPickupItem, WantsToRemoveItem,
+    WantsToTeleport, WantsToUseItem, Weight,
 };
 use specs::prelude::*;
 
+/// Empties a container onto the floor at `pos`
+pub fn spill_contents(
+    entities: &Entities,
+    backpack: &mut WriteStorage<InBackpack>,
+    positions: &mut WriteStorage<Position>,
+    container: Entity,
+    pos: &Position,
+) {
+    let contents: Vec<Entity> = (entities, &*backpack)
+        .join()
+        .filter(|(_item, pack)| pack.owner == container)
+        .map(|(item, _pack)| item)
+        .collect();
+    for item in contents {
+        backpack.remove(item);
+        positions
+            .insert(item, pos.clone())
+            .expect("Unable to insert position");
+    }
+}
+
+/// Adds a stackable item to an identical stack already in `owner`'s backpack, if they carry
+/// one. Returns true if it was merged, in which case the item is out of the backpack and
+/// should be deleted.
+pub fn merge_into_stack(
+    entities: &Entities,
+    backpack: &mut WriteStorage<InBackpack>,
+    names: &ReadStorage<Name>,
+    stacks: &mut WriteStorage<Stackable>,
+    owner: Entity,
+    item: Entity,
+) -> bool {
+    let quantity = match stacks.get(item) {
+        None => return false,
+        Some(stack) => stack.quantity,
+    };
+    let name = &names.get(item).unwrap().name;
+    let existing = (entities, &*backpack, names, &*stacks)
+        .join()
+        .find(|(other, pack, other_name, _stack)| {
+            *other != item && pack.owner == owner && other_name.name == *name
+        })
+        .map(|(other, _pack, _name, _stack)| other);
+    match existing {
+        None => false,
+        Some(other) => {
+            stacks.get_mut(other).unwrap().quantity += quantity;
+            backpack.remove(item);
+            true
+        }
+    }
+}
+
+/// Puts an item in `owner`'s backpack, piling it onto an identical stack if they carry one
+pub fn stow_item(ecs: &World, owner: Entity, item: Entity) {
+    let entities = ecs.entities();
+    let mut backpack = ecs.write_storage::<InBackpack>();
+    let mut stacks = ecs.write_storage::<Stackable>();
+    if merge_into_stack(
+        &entities,
+        &mut backpack,
+        &ecs.read_storage::<Name>(),
+        &mut stacks,
+        owner,
+        item,
+    ) {
+        entities.delete(item).expect("Delete failed");
+    } else {
+        backpack
+            .insert(item, InBackpack { owner })
+            .expect("Unable to insert backpack entry");
+    }
+}
+
+/// Uses up one item off a stack, deleting it once none are left
+pub fn use_up_one(ecs: &World, item: Entity) {
+    match ecs.write_storage::<Stackable>().get_mut(item) {
+        Some(stack) if stack.quantity > 1 => stack.quantity -= 1,
+        _ => ecs.entities().delete(item).expect("Delete failed"),
+    }
+}
+
+/// What an item weighs, counting every item in its stack
+pub fn stack_weight(weight: Option<&Weight>, stack: Option<&Stackable>) -> f32 {
+    weight.map_or(0.0, |w| w.pounds) * stack.map_or(1, |stack| stack.quantity) as f32
+}
+
 pub struct ItemCollectionSystem {}
 
 impl<'a> System<'a> for ItemCollectionSystem {
@@ -18,27 +111,103 @@ impl<'a> System<'a> for ItemCollectionSystem {
         WriteStorage<'a, Position>,
         ReadStorage<'a, Name>,
         WriteStorage<'a, InBackpack>,
+        Entities<'a>,
+        ReadStorage<'a, Gold>,
+        WriteStorage<'a, Purse>,
+        ReadExpect<'a, ItemIdentification>,
+        ReadStorage<'a, Weight>,
+        WriteStorage<'a, Encumbrance>,
+        ReadStorage<'a, Container>,
+        WriteStorage<'a, Stackable>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (player_entity, mut gamelog, mut wants_pickup, mut positions, names, mut backpack) =
-            data;
+        let (
+            player_entity,
+            mut gamelog,
+            mut wants_pickup,
+            mut positions,
+            names,
+            mut backpack,
+            entities,
+            gold,
+            mut purses,
+            identification,
+            weights,
+            mut encumbrance,
+            containers,
+            mut stacks,
+        ) = data;
 
         for pickup in wants_pickup.join() {
+            // There's only so much anyone can lift
+            let pounds = stack_weight(weights.get(pickup.item), stacks.get(pickup.item));
+            if let Some(load) = encumbrance.get_mut(pickup.collected_by) {
+                if !load.can_lift(pounds) {
+                    if pickup.collected_by == *player_entity {
+                        gamelog.entries.push(format!(
+                            "You can't carry the {} as well; it's too heavy.",
+                            identification.display_name(&names.get(pickup.item).unwrap().name)
+                        ));
+                    }
+                    continue;
+                }
+                load.carried += pounds;
+            }
+
+            // Whatever was inside a container (a corpse, say) spills out where it lay
+            if containers.get(pickup.item).is_some() {
+                if let Some(pos) = positions.get(pickup.item).cloned() {
+                    spill_contents(&entities, &mut backpack, &mut positions, pickup.item, &pos);
+                }
+            }
+
             positions.remove(pickup.item);
-            backpack
-                .insert(
-                    pickup.item,
-                    InBackpack {
-                        owner: pickup.collected_by,
-                    },
-                )
-                .expect("Unable to insert backpack entry");
+
+            // Gold goes straight into the purse, for those who carry one
+            if let (Some(gold), Some(purse)) =
+                (gold.get(pickup.item), purses.get_mut(pickup.collected_by))
+            {
+                purse.gold += gold.amount;
+                if pickup.collected_by == *player_entity {
+                    gamelog
+                        .entries
+                        .push(format!("You pick up {} gold.", gold.amount));
+                }
+                entities.delete(pickup.item).expect("Delete failed");
+                continue;
+            }
+
+            // Identical items pile onto a stack the collector already carries
+            let item_name = identification.display_name(&names.get(pickup.item).unwrap().name);
+            if merge_into_stack(
+                &entities,
+                &mut backpack,
+                &names,
+                &mut stacks,
+                pickup.collected_by,
+                pickup.item,
+            ) {
+                entities.delete(pickup.item).expect("Delete failed");
+            } else {
+                backpack
+                    .insert(
+                        pickup.item,
+                        InBackpack {
+                            owner: pickup.collected_by,
+                        },
+                    )
+                    .expect("Unable to insert backpack entry");
+            }
 
             if pickup.collected_by == *player_entity {
+                gamelog
+                    .entries
+                    .push(format!("You pick up the {}.", item_name));
+            } else if let Some(collector_name) = names.get(pickup.collected_by) {
                 gamelog.entries.push(format!(
-                    "You pick up the {}.",
-                    names.get(pickup.item).unwrap().name,
+                    "{} picks up the {}.",
+                    collector_name.name, item_name,
                 ));
             }
         }
@@ -47,6 +216,9 @@ impl<'a> System<'a> for ItemCollectionSystem {
     }
 }
 
+/// How long food poisoning lasts
+const SICKNESS_TURNS: i32 = 100;
+
 pub struct ItemUseSystem {}
 
 impl<'a> System<'a> for ItemUseSystem {
@@ -72,8 +244,31 @@ impl<'a> System<'a> for ItemUseSystem {
         ReadStorage<'a, Position>,
         ReadStorage<'a, ProvidesFood>,
         WriteStorage<'a, HungerClock>,
-        ReadStorage<'a, MagicMapper>,
+        (
+            ReadStorage<'a, MagicMapper>,
+            ReadStorage<'a, Teleports>,
+            WriteStorage<'a, WantsToTeleport>,
+        ),
         WriteExpect<'a, RunState>,
+        WriteExpect<'a, ItemIdentification>,
+        (
+            WriteStorage<'a, Cursed>,
+            WriteStorage<'a, CurseKnown>,
+            ReadStorage<'a, RemovesCurse>,
+        ),
+        (
+            WriteStorage<'a, Charges>,
+            ReadStorage<'a, Recharges>,
+            ReadStorage<'a, KnownSpell>,
+            WriteStorage<'a, Mana>,
+            ReadStorage<'a, Attributes>,
+            WriteStorage<'a, Stackable>,
+        ),
+        (
+            ReadStorage<'a, Perishable>,
+            WriteStorage<'a, Sickness>,
+            WriteExpect<'a, rltk::RandomNumberGenerator>,
+        ),
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -98,25 +293,78 @@ impl<'a> System<'a> for ItemUseSystem {
             positions,
             provides_food,
             mut hunger_clocks,
-            magic_mapper,
+            (magic_mapper, teleports, mut wants_teleport),
             mut runstate,
+            mut identification,
+            (mut cursed, mut curse_known, removes_curse),
+            (mut charges, recharges, known_spells, mut mana, attributes, mut stacks),
+            (perishable, mut sickness, mut rng),
         ) = data;
 
         for (entity, useitem) in (&entities, &wants_use).join() {
             let mut used_item = true;
 
+            // Spent wands are inert until recharged
+            if charges.get(useitem.item).is_some_and(|c| c.current < 1) {
+                if entity == *player_entity {
+                    gamelog.entries.push(format!(
+                        "You wave the {}, but nothing happens.",
+                        identification.display_name(&names.get(useitem.item).unwrap().name)
+                    ));
+                }
+                continue;
+            }
+
+            // Spells draw on the caster's mana, and hit harder for a sharper mind
+            let mut spell_bonus = 0;
+            let mut mana_cost = 0;
+            if let Some(spell) = known_spells.get(useitem.item) {
+                let caster = attributes.get(entity);
+                let cost = caster.map_or(spell.mana_cost, |a| a.mana_cost(spell.mana_cost));
+                match mana.get(entity) {
+                    Some(pool) if pool.current >= cost => mana_cost = cost,
+                    _ => {
+                        if entity == *player_entity {
+                            gamelog.entries.push(format!(
+                                "You don't have enough mana to cast {}.",
+                                names.get(useitem.item).unwrap().name
+                            ));
+                        }
+                        continue;
+                    }
+                }
+                spell_bonus = caster.map_or(0, |a| a.spell_power());
+            }
+            let (verb, verbs) = if known_spells.get(useitem.item).is_some() {
+                ("cast", "casts")
+            } else {
+                ("drink", "drinks")
+            };
+
+            // Using something is the surest way to find out what it is; watching a monster
+            // use it doesn't teach you anything
+            let real_name = names.get(useitem.item).unwrap().name.to_string();
+            if entity == *player_entity && identification.identify(&real_name) {
+                gamelog
+                    .entries
+                    .push(format!("You discover that this is a {}.", real_name));
+            }
+            let item_name = identification.display_name(&real_name);
+
             // Targeting
             let mut targets: Vec<Entity> = Vec::new();
             match useitem.target {
-                None => targets.push(*player_entity),
+                None => targets.push(entity),
                 Some(target) => {
                     let area_effect = aoe.get(useitem.item);
                     match area_effect {
                         None => {
-                            // Single target in tile
+                            // Single target in tile; only living things can be affected
                             let idx = map.xy_idx(target.x, target.y);
                             for mob in map.tile_content[idx].iter() {
-                                targets.push(*mob);
+                                if combat_stats.get(*mob).is_some() {
+                                    targets.push(*mob);
+                                }
                             }
                         }
                         Some(area_effect) => {
@@ -129,7 +377,9 @@ impl<'a> System<'a> for ItemUseSystem {
                             for tile_idx in blast_tiles.iter() {
                                 let idx = map.xy_idx(tile_idx.x, tile_idx.y);
                                 for mob in map.tile_content[idx].iter() {
-                                    targets.push(*mob);
+                                    if combat_stats.get(*mob).is_some() {
+                                        targets.push(*mob);
+                                    }
                                 }
                                 particle_builder.request(
                                     tile_idx.x,
@@ -153,6 +403,20 @@ impl<'a> System<'a> for ItemUseSystem {
                     let target_slot = can_equip.slot;
                     let target = targets[0];
 
+                    // Nothing can take the place of a cursed item
+                    let stuck_with = (&entities, &equipped, &cursed, &names)
+                        .join()
+                        .find(|(_e, eq, _c, _n)| eq.owner == target && eq.slot == target_slot)
+                        .map(|(_e, _eq, _c, name)| name.name.to_string());
+                    if let Some(stuck_with) = stuck_with {
+                        if target == *player_entity {
+                            gamelog
+                                .entries
+                                .push(format!("You can't remove the {}: it's cursed!", stuck_with));
+                        }
+                        continue;
+                    }
+
                     // Remove any items the target has in the item's slot
                     let mut to_unequip: Vec<Entity> = Vec::new();
                     for (item_entity, already_equipped, name) in
@@ -163,6 +427,12 @@ impl<'a> System<'a> for ItemUseSystem {
                             to_unequip.push(item_entity);
                             if target == *player_entity {
                                 gamelog.entries.push(format!("You unequip {}.", name.name));
+                            } else if let Some(target_name) = names.get(target) {
+                                gamelog.entries.push(format!(
+                                    "{} unequips {}.",
+                                    target_name.name,
+                                    identification.display_name(&name.name)
+                                ));
                             }
                         }
                     }
@@ -185,10 +455,20 @@ impl<'a> System<'a> for ItemUseSystem {
                         .expect("Unable to insert equipped component");
                     backpack.remove(useitem.item);
                     if target == *player_entity {
-                        gamelog.entries.push(format!(
-                            "You equip {}.",
-                            names.get(useitem.item).unwrap().name
-                        ));
+                        gamelog.entries.push(format!("You equip {}.", item_name));
+                        if cursed.get(useitem.item).is_some() {
+                            gamelog.entries.push(format!(
+                                "The {} is cursed! You can't let go of it.",
+                                item_name
+                            ));
+                        }
+                        curse_known
+                            .insert(useitem.item, CurseKnown {})
+                            .expect("Unable to insert curse knowledge");
+                    } else if let Some(target_name) = names.get(target) {
+                        gamelog
+                            .entries
+                            .push(format!("{} equips {}.", target_name.name, item_name));
                     }
                 }
             }
@@ -197,25 +477,47 @@ impl<'a> System<'a> for ItemUseSystem {
             let item_edible = provides_food.get(useitem.item);
             match item_edible {
                 None => (),
-                Some(_) => {
+                Some(food) => {
                     used_item = true;
                     let target = targets[0];
                     let hc = hunger_clocks.get_mut(target);
                     if let Some(hc) = hc {
-                        hc.state = HungerState::WellFed;
-                        hc.duration = 20;
-                        gamelog.entries.push(format!(
-                            "You eat the {}.",
-                            names.get(useitem.item).unwrap().name
-                        ));
+                        // Rotten food is only half as filling, and may well disagree with you
+                        let rotten = perishable.get(useitem.item).is_some_and(|p| p.rotten());
+                        let nutrition = if rotten {
+                            food.nutrition / 2
+                        } else {
+                            food.nutrition
+                        };
+                        hc.nutrition = i32::min(MAX_NUTRITION, hc.nutrition + nutrition);
+                        hc.state = HungerState::from_nutrition(hc.nutrition);
+                        if target == *player_entity {
+                            gamelog.entries.push(format!("You eat the {}.", item_name));
+                        }
+                        if rotten && rng.roll_dice(1, 3) > 1 {
+                            sickness
+                                .insert(
+                                    target,
+                                    Sickness {
+                                        turns: SICKNESS_TURNS,
+                                    },
+                                )
+                                .expect("Unable to insert sickness");
+                            if target == *player_entity {
+                                gamelog
+                                    .entries
+                                    .push("That was rotten! You feel queasy.".to_string());
+                            }
+                        }
                     }
                 }
             }
 
-            // If its a magic mapper...
+            // If its a magic mapper... (only the player's map matters)
             let is_mapper = magic_mapper.get(useitem.item);
             match is_mapper {
                 None => (),
+                Some(_) if entity != *player_entity => (),
                 Some(_) => {
                     used_item = true;
                     gamelog
@@ -225,21 +527,94 @@ impl<'a> System<'a> for ItemUseSystem {
                 }
             }
 
+            // Teleports are carried out once everyone has finished with their items
+            if let Some(teleport) = teleports.get(useitem.item) {
+                used_item = true;
+                let destination = match teleport.kind {
+                    TeleportKind::Blink => useitem.target,
+                    TeleportKind::Random | TeleportKind::Level => None,
+                };
+                wants_teleport
+                    .insert(
+                        entity,
+                        WantsToTeleport {
+                            kind: teleport.kind,
+                            destination,
+                        },
+                    )
+                    .expect("Unable to insert intent");
+            }
+
+            // Lifts the curse from everything the user carries
+            if removes_curse.get(useitem.item).is_some() {
+                let target = targets[0];
+                let freed: Vec<Entity> = (&entities, &cursed)
+                    .join()
+                    .filter(|(item, _cursed)| {
+                        equipped.get(*item).is_some_and(|eq| eq.owner == target)
+                            || backpack.get(*item).is_some_and(|pack| pack.owner == target)
+                    })
+                    .map(|(item, _cursed)| item)
+                    .collect();
+                for item in freed.iter() {
+                    cursed.remove(*item);
+                    if target == *player_entity {
+                        gamelog.entries.push(format!(
+                            "A soft white light surrounds your {}.",
+                            identification.display_name(&names.get(*item).unwrap().name)
+                        ));
+                    }
+                }
+                if freed.is_empty() && target == *player_entity {
+                    gamelog
+                        .entries
+                        .push("You feel as if someone is watching over you.".to_string());
+                }
+            }
+
+            // Restores every charged item the user carries
+            if recharges.get(useitem.item).is_some() {
+                let target = targets[0];
+                let mut recharged = false;
+                for (item, charge, pack) in (&entities, &mut charges, &backpack).join() {
+                    if pack.owner == target && charge.current < charge.max {
+                        charge.current = charge.max;
+                        recharged = true;
+                        if target == *player_entity {
+                            gamelog.entries.push(format!(
+                                "Your {} hums with renewed power.",
+                                identification.display_name(&names.get(item).unwrap().name)
+                            ));
+                        }
+                    }
+                }
+                if !recharged && target == *player_entity {
+                    gamelog
+                        .entries
+                        .push("You feel a brief tingle in your fingers.".to_string());
+                }
+            }
+
             // If it heals, apply the healing
             let item_heals = healing.get(useitem.item);
             match item_heals {
                 None => (),
                 Some(healer) => {
                     used_item = false;
+                    let heal_amount = healer.heal_amount + spell_bonus;
                     for target in targets.iter() {
                         let stats = combat_stats.get_mut(*target);
                         if let Some(stats) = stats {
-                            stats.hp = i32::min(stats.max_hp, stats.hp + healer.heal_amount);
+                            stats.hp = i32::min(stats.max_hp, stats.hp + heal_amount);
                             if entity == *player_entity {
                                 gamelog.entries.push(format!(
-                                    "You drink the {}, healing {} hp.",
-                                    names.get(useitem.item).unwrap().name,
-                                    healer.heal_amount
+                                    "You {} the {}, healing {} hp.",
+                                    verb, item_name, heal_amount
+                                ));
+                            } else if let Some(user_name) = names.get(entity) {
+                                gamelog.entries.push(format!(
+                                    "{} {} the {}, healing {} hp.",
+                                    user_name.name, verbs, item_name, heal_amount
                                 ));
                             }
                             used_item = true;
@@ -266,14 +641,19 @@ impl<'a> System<'a> for ItemUseSystem {
                 None => (),
                 Some(damage) => {
                     used_item = false;
+                    let amount = damage.damage + spell_bonus;
                     for mob in targets.iter() {
-                        SufferDamage::new_damage(&mut suffer_damage, *mob, damage.damage);
+                        SufferDamage::new_damage(&mut suffer_damage, *mob, amount);
+                        let mob_name = names.get(*mob).unwrap();
                         if entity == *player_entity {
-                            let mob_name = names.get(*mob).unwrap();
-                            let item_name = names.get(useitem.item).unwrap();
                             gamelog.entries.push(format!(
                                 "You use {} on {}, inflicting {} hp.",
-                                item_name.name, mob_name.name, damage.damage
+                                item_name, mob_name.name, amount
+                            ));
+                        } else if let Some(user_name) = names.get(entity) {
+                            gamelog.entries.push(format!(
+                                "{} uses {} on {}, inflicting {} hp.",
+                                user_name.name, item_name, mob_name.name, amount
                             ));
                         }
                         used_item = true;
@@ -303,26 +683,30 @@ impl<'a> System<'a> for ItemUseSystem {
                         used_item = false;
                         for mob in targets.iter() {
                             add_confusion.push((*mob, confusion.turns));
+                            let mob_name = names.get(*mob).unwrap();
                             if entity == *player_entity {
-                                let mob_name = names.get(*mob).unwrap();
-                                let item_name = names.get(useitem.item).unwrap();
                                 gamelog.entries.push(format!(
                                     "You use {} on {}, confusing them.",
-                                    item_name.name, mob_name.name
+                                    item_name, mob_name.name
                                 ));
-                                used_item = true;
-
-                                let pos = positions.get(*mob);
-                                if let Some(pos) = pos {
-                                    particle_builder.request(
-                                        pos.x,
-                                        pos.y,
-                                        rltk::RGB::named(rltk::MAGENTA),
-                                        rltk::RGB::named(rltk::BLACK),
-                                        rltk::to_cp437('?'),
-                                        200.0,
-                                    );
-                                }
+                            } else if let Some(user_name) = names.get(entity) {
+                                gamelog.entries.push(format!(
+                                    "{} uses {} on {}, confusing them.",
+                                    user_name.name, item_name, mob_name.name
+                                ));
+                            }
+                            used_item = true;
+
+                            let pos = positions.get(*mob);
+                            if let Some(pos) = pos {
+                                particle_builder.request(
+                                    pos.x,
+                                    pos.y,
+                                    rltk::RGB::named(rltk::MAGENTA),
+                                    rltk::RGB::named(rltk::BLACK),
+                                    rltk::to_cp437('?'),
+                                    200.0,
+                                );
                             }
                         }
                     }
@@ -334,12 +718,31 @@ impl<'a> System<'a> for ItemUseSystem {
                     .expect("Unable to insert status");
             }
 
-            // If its a consumable, we delete it on use
+            // If its a consumable, we use one up, deleting it once none are left
+            if used_item && consumables.get(useitem.item).is_some() {
+                match stacks.get_mut(useitem.item) {
+                    Some(stack) if stack.quantity > 1 => stack.quantity -= 1,
+                    _ => entities.delete(useitem.item).expect("Delete failed"),
+                }
+            }
+
+            // Spells only spend their mana if they actually did something
+            if used_item && mana_cost > 0 {
+                if let Some(pool) = mana.get_mut(entity) {
+                    pool.current -= mana_cost;
+                }
+            }
+
+            // Items with charges use one up instead
             if used_item {
-                let consumable = consumables.get(useitem.item);
-                match consumable {
-                    None => (),
-                    Some(_) => entities.delete(useitem.item).expect("Delete failed"),
+                if let Some(charge) = charges.get_mut(useitem.item) {
+                    charge.current -= 1;
+                    if charge.current < 1 && entity == *player_entity {
+                        gamelog.entries.push(format!(
+                            "The {} sputters and goes dark.",
+                            identification.display_name(&real_name)
+                        ));
+                    }
                 }
             }
         }
@@ -360,6 +763,7 @@ impl<'a> System<'a> for ItemDropSystem {
         ReadStorage<'a, Name>,
         WriteStorage<'a, Position>,
         WriteStorage<'a, InBackpack>,
+        ReadExpect<'a, ItemIdentification>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -371,6 +775,7 @@ impl<'a> System<'a> for ItemDropSystem {
             names,
             mut positions,
             mut backpack,
+            identification,
         ) = data;
 
         for (entity, to_drop) in (&entities, &wants_drop).join() {
@@ -394,7 +799,7 @@ impl<'a> System<'a> for ItemDropSystem {
             if entity == *player_entity {
                 gamelog.entries.push(format!(
                     "You drop the {}.",
-                    names.get(to_drop.item).unwrap().name
+                    identification.display_name(&names.get(to_drop.item).unwrap().name)
                 ));
             }
         }
@@ -411,12 +816,36 @@ impl<'a> System<'a> for ItemRemoveSystem {
         WriteStorage<'a, WantsToRemoveItem>,
         WriteStorage<'a, Equipped>,
         WriteStorage<'a, InBackpack>,
+        ReadStorage<'a, Cursed>,
+        ReadStorage<'a, Name>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        ReadExpect<'a, ItemIdentification>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (entities, mut wants_remove, mut equipped, mut backpack) = data;
+        let (
+            entities,
+            mut wants_remove,
+            mut equipped,
+            mut backpack,
+            cursed,
+            names,
+            player_entity,
+            mut gamelog,
+            identification,
+        ) = data;
 
         for (entity, to_remove) in (&entities, &wants_remove).join() {
+            if cursed.get(to_remove.item).is_some() {
+                if entity == *player_entity {
+                    gamelog.entries.push(format!(
+                        "You can't remove the {}: it's cursed!",
+                        identification.display_name(&names.get(to_remove.item).unwrap().name)
+                    ));
+                }
+                continue;
+            }
             equipped.remove(to_remove.item);
             backpack
                 .insert(to_remove.item, InBackpack { owner: entity })
@@ -0,0 +1,122 @@
+use super::{
+    gamelog::GameLog, Equipped, Fuel, Item, LightSource, Map, Name, Position, RunState, TileType,
+    Viewshed,
+};
+use rltk::{field_of_view, Point, RGB};
+use specs::prelude::*;
+
+/// How much of the ambient light is lost with each level down; it's pitch black from
+/// depth 6 onward
+const AMBIENT_FALLOFF: f32 = 0.2;
+
+/// Down stairs give off a faint glow, so there's always a way onward
+const STAIRS_GLOW_RANGE: i32 = 2;
+
+/// The light a level has before anything shines on it
+pub fn ambient_light(depth: i32) -> f32 {
+    f32::max(0.0, 1.0 - (depth - 1) as f32 * AMBIENT_FALLOFF)
+}
+
+/// Works out how brightly (and in what color) every tile is lit, and burns the fuel of any
+/// light that's in use
+pub struct LightingSystem {}
+
+impl<'a> System<'a> for LightingSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, RunState>,
+        WriteExpect<'a, Map>,
+        WriteExpect<'a, GameLog>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, LightSource>,
+        ReadStorage<'a, Item>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, Name>,
+        WriteStorage<'a, Fuel>,
+        WriteStorage<'a, Viewshed>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            player_entity,
+            runstate,
+            mut map,
+            mut log,
+            positions,
+            light_sources,
+            items,
+            equipped,
+            names,
+            mut fuel,
+            mut viewsheds,
+        ) = data;
+
+        // Lights in use burn down once a turn
+        if *runstate == RunState::PlayerTurn {
+            for (item, fuel, eq) in (&entities, &mut fuel, &equipped).join() {
+                if fuel.turns <= 0 {
+                    continue;
+                }
+                fuel.turns -= 1;
+                if fuel.turns == 0 && eq.owner == *player_entity {
+                    if let Some(name) = names.get(item) {
+                        log.entries.push(format!("Your {} burns out.", name.name));
+                    }
+                }
+            }
+        }
+
+        let mut lights: Vec<(Point, RGB, i32)> = Vec::new();
+        for (idx, tile) in map.tiles.iter().enumerate() {
+            if *tile == TileType::DownStairs {
+                lights.push((
+                    Point::new(idx as i32 % map.width, idx as i32 / map.width),
+                    RGB::named(rltk::CYAN),
+                    STAIRS_GLOW_RANGE,
+                ));
+            }
+        }
+        for (entity, light, pos) in (&entities, &light_sources, &positions).join() {
+            if items.get(entity).is_none() {
+                lights.push((Point::new(pos.x, pos.y), light.color, light.range));
+            }
+        }
+        for (item, light, eq) in (&entities, &light_sources, &equipped).join() {
+            if fuel.get(item).is_some_and(|f| f.turns <= 0) {
+                continue;
+            }
+            if let Some(pos) = positions.get(eq.owner) {
+                lights.push((Point::new(pos.x, pos.y), light.color, light.range));
+            }
+        }
+
+        let ambient = ambient_light(map.depth);
+        for tile in map.light.iter_mut() {
+            *tile = RGB::from_f32(ambient, ambient, ambient);
+        }
+        for (center, color, range) in lights.iter() {
+            // Light fades with distance and is stopped by anything that blocks sight
+            let lit = field_of_view(*center, *range, &*map);
+            for point in lit.iter() {
+                if point.x < 0 || point.x >= map.width || point.y < 0 || point.y >= map.height {
+                    continue;
+                }
+                let distance = rltk::DistanceAlg::Pythagoras.distance2d(*center, *point);
+                let intensity = f32::max(0.0, 1.0 - distance / (*range + 1) as f32);
+                let idx = map.xy_idx(point.x, point.y);
+                let tile = &mut map.light[idx];
+                tile.r = f32::min(1.0, tile.r + color.r * intensity);
+                tile.g = f32::min(1.0, tile.g + color.g * intensity);
+                tile.b = f32::min(1.0, tile.b + color.b * intensity);
+            }
+        }
+
+        // What the player can see depends on the light, which changes as things move
+        if let Some(viewshed) = viewsheds.get_mut(*player_entity) {
+            viewshed.dirty = true;
+        }
+    }
+}
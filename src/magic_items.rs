@@ -0,0 +1,211 @@
+use super::{
+    spawner::{curse, cursed_bonus, roll_curse},
+    AttributeBonus, DefenseBonus, Durability, Element, ElementalDamage, EquipmentSlot, Equippable,
+    Item, MagicItem, Map, MeleePowerBonus, Name, Position, Rarity, Renderable, SerializeMe, Value,
+    Weight,
+};
+use rltk::{RandomNumberGenerator, RGB};
+use specs::prelude::*;
+use specs::saveload::{MarkedBuilder, SimpleMarker};
+
+/// The mundane item a magic item is made from
+struct Base {
+    name: &'static str,
+    glyph: char,
+    slot: EquipmentSlot,
+    power: i32,
+    defense: i32,
+    value: i32,
+    pounds: f32,
+    durability: i32,
+}
+
+const BASES: [Base; 4] = [
+    Base {
+        name: "Dagger",
+        glyph: '/',
+        slot: EquipmentSlot::Melee,
+        power: 2,
+        defense: 0,
+        value: 15,
+        pounds: 1.0,
+        durability: 20,
+    },
+    Base {
+        name: "Longsword",
+        glyph: '/',
+        slot: EquipmentSlot::Melee,
+        power: 4,
+        defense: 0,
+        value: 50,
+        pounds: 4.0,
+        durability: 40,
+    },
+    Base {
+        name: "Shield",
+        glyph: '(',
+        slot: EquipmentSlot::Shield,
+        power: 0,
+        defense: 1,
+        value: 15,
+        pounds: 10.0,
+        durability: 25,
+    },
+    Base {
+        name: "Tower Shield",
+        glyph: '(',
+        slot: EquipmentSlot::Shield,
+        power: 0,
+        defense: 3,
+        value: 50,
+        pounds: 35.0,
+        durability: 50,
+    },
+];
+
+/// What an affix does to the item it's rolled onto
+#[derive(Clone, Copy)]
+enum Enchantment {
+    Power,
+    Defense,
+    Strength,
+    Elemental(Element),
+}
+
+/// Prefixes and suffixes, and whether they only make sense on weapons
+const PREFIXES: [(&str, Enchantment, bool); 5] = [
+    ("Flaming", Enchantment::Elemental(Element::Fire), true),
+    ("Freezing", Enchantment::Elemental(Element::Frost), true),
+    ("Shocking", Enchantment::Elemental(Element::Shock), true),
+    ("Vicious", Enchantment::Power, false),
+    ("Sturdy", Enchantment::Defense, false),
+];
+const SUFFIXES: [(&str, Enchantment, bool); 3] = [
+    ("of Warding", Enchantment::Defense, false),
+    ("of Slaying", Enchantment::Power, true),
+    ("of the Ox", Enchantment::Strength, false),
+];
+
+/// Deeper levels roll better items
+fn roll_rarity(rng: &mut RandomNumberGenerator, depth: i32) -> Rarity {
+    let roll = rng.roll_dice(1, 100) + depth * 4;
+    if roll > 115 {
+        Rarity::Epic
+    } else if roll > 95 {
+        Rarity::Rare
+    } else if roll > 65 {
+        Rarity::Uncommon
+    } else {
+        Rarity::Common
+    }
+}
+
+fn roll_affix(
+    rng: &mut RandomNumberGenerator,
+    affixes: &[(&'static str, Enchantment, bool)],
+    weapon: bool,
+) -> (&'static str, Enchantment) {
+    let allowed: Vec<&(&str, Enchantment, bool)> = affixes
+        .iter()
+        .filter(|(_name, _enchantment, weapons_only)| weapon || !weapons_only)
+        .collect();
+    let (name, enchantment, _weapons_only) =
+        allowed[rng.roll_dice(1, allowed.len() as i32) as usize - 1];
+    (name, *enchantment)
+}
+
+/// Rolls a base item, an enhancement and (for better rarities) a prefix and suffix, such
+/// as a "+2 Flaming Longsword of Warding"
+pub fn magic_item(ecs: &mut World, x: i32, y: i32) {
+    let cursed = roll_curse(ecs);
+    let depth = ecs.fetch::<Map>().depth;
+    let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+
+    let base = &BASES[rng.roll_dice(1, BASES.len() as i32) as usize - 1];
+    let weapon = base.slot == EquipmentSlot::Melee;
+    let rarity = roll_rarity(&mut rng, depth);
+    let tier = rarity.tier();
+
+    let enhancement = rng.roll_dice(1, tier);
+    let mut power = base.power;
+    let mut defense = base.defense;
+    if weapon {
+        power += enhancement;
+    } else {
+        defense += enhancement;
+    }
+
+    let mut enchantments: Vec<Enchantment> = Vec::new();
+    let mut name = base.name.to_string();
+    if rarity != Rarity::Common {
+        let (prefix, enchantment) = roll_affix(&mut rng, &PREFIXES, weapon);
+        name = format!("{} {}", prefix, name);
+        enchantments.push(enchantment);
+    }
+    if tier >= Rarity::Rare.tier() {
+        let (suffix, enchantment) = roll_affix(&mut rng, &SUFFIXES, weapon);
+        name = format!("{} {}", name, suffix);
+        enchantments.push(enchantment);
+    }
+    let name = format!("+{} {}", enhancement, name);
+
+    let mut strength = 0;
+    let mut element = None;
+    for enchantment in enchantments.iter() {
+        match enchantment {
+            Enchantment::Power => power += tier,
+            Enchantment::Defense => defense += (tier + 1) / 2,
+            Enchantment::Strength => strength += tier,
+            Enchantment::Elemental(kind) => element = Some(*kind),
+        }
+    }
+    std::mem::drop(rng);
+
+    let mut builder = ecs
+        .create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(base.glyph),
+            fg: rarity.color(),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name { name })
+        .with(Item {})
+        .with(MagicItem { rarity })
+        .with(Value {
+            base: base.value * (1 + tier),
+        })
+        .with(Weight {
+            pounds: base.pounds,
+        })
+        .with(Durability {
+            current: base.durability * 2,
+            max: base.durability * 2,
+        })
+        .with(Equippable { slot: base.slot });
+    if power != 0 {
+        builder = builder.with(MeleePowerBonus {
+            power: cursed_bonus(power, cursed),
+        });
+    }
+    if defense != 0 {
+        builder = builder.with(DefenseBonus {
+            defense: cursed_bonus(defense, cursed),
+        });
+    }
+    if strength != 0 {
+        builder = builder.with(AttributeBonus {
+            strength: cursed_bonus(strength, cursed),
+        });
+    }
+    if let Some(element) = element {
+        builder = builder.with(ElementalDamage {
+            element,
+            damage: 1 + tier,
+        });
+    }
+    curse(builder, cursed)
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
@@ -6,35 +6,57 @@ use specs::saveload::{SimpleMarker, SimpleMarkerAllocator};
 
 mod components;
 mod damage_system;
+mod door_system;
+mod encumbrance_system;
 mod gamelog;
 mod gui;
 mod hunger_system;
 mod inventory_system;
+mod lighting_system;
+mod magic_items;
+mod mana_system;
 mod map;
 mod map_indexing_system;
 mod melee_combat_system;
 mod monster_ai_system;
+mod monster_item_system;
+mod navigation_system;
+mod noise_system;
 mod particle_system;
 mod player;
 mod rect;
 mod rex_assets;
 mod spawner;
+mod spoilage_system;
+mod teleport_system;
+mod throw_system;
 mod trigger_system;
 mod visibility_system;
 pub use components::*;
 use damage_system::DamageSystem;
+use door_system::DoorSystem;
+use encumbrance_system::EncumbranceSystem;
 use hunger_system::HungerSystem;
 use inventory_system::{ItemCollectionSystem, ItemDropSystem, ItemRemoveSystem, ItemUseSystem};
+use lighting_system::LightingSystem;
+use mana_system::ManaSystem;
 pub use map::*;
 use map_indexing_system::MapIndexingSystem;
 use melee_combat_system::MeleeCombatSystem;
 use monster_ai_system::MonsterAI;
+use monster_item_system::MonsterItemSystem;
+use navigation_system::NavigationSystem;
+use noise_system::HearingSystem;
 use particle_system::ParticleSpawnSystem;
 pub use player::*;
 use rect::Rect;
+use spoilage_system::SpoilageSystem;
+use teleport_system::TeleportSystem;
+use throw_system::ItemThrowSystem;
 use trigger_system::TriggerSystem;
 use visibility_system::VisibilitySystem;
 pub mod camera;
+pub mod faction_table;
 pub mod map_builders;
 pub mod random_table;
 pub mod saveload_system;
@@ -50,15 +72,38 @@ pub enum RunState {
     ShowInventory,
     ShowDropItem,
     ShowRemoveItem,
+    ShowThrowItem,
+    ShowThrowTargeting {
+        item: Entity,
+    },
     ShowTargeting {
         range: i32,
         item: Entity,
     },
+    ShowVendor {
+        vendor: Entity,
+        mode: gui::VendorMode,
+    },
+    ShowIdentify {
+        scroll: Entity,
+    },
+    ShowRepair {
+        kit: Entity,
+    },
+    ShowContainer {
+        container: Entity,
+    },
+    ShowPickup,
+    ShowAutoPickup,
+    ShowCastSpell,
     MainMenu {
         menu_selection: gui::MainMenuSelection,
     },
     SaveGame,
     NextLevel,
+    TeleportLevel {
+        depth: i32,
+    },
     GameOver,
     MagicMapReveal {
         row: i32,
@@ -72,14 +117,25 @@ pub struct State {
     mapgen_history: Vec<Map>,
     mapgen_index: usize,
     mapgen_timer: f32,
+    menu_page: usize,
 }
 
 impl State {
     fn run_systems(&mut self) {
+        let mut lighting = LightingSystem {};
+        lighting.run_now(&self.ecs);
         let mut vis = VisibilitySystem {};
         vis.run_now(&self.ecs);
+        let mut navigation = NavigationSystem {};
+        navigation.run_now(&self.ecs);
+        let mut hearing = HearingSystem {};
+        hearing.run_now(&self.ecs);
+        let mut monster_items = MonsterItemSystem {};
+        monster_items.run_now(&self.ecs);
         let mut mob = MonsterAI {};
         mob.run_now(&self.ecs);
+        let mut doors = DoorSystem {};
+        doors.run_now(&self.ecs);
         let mut mapindex = MapIndexingSystem {};
         mapindex.run_now(&self.ecs);
         let mut triggers = TriggerSystem {};
@@ -92,12 +148,22 @@ impl State {
         pickup.run_now(&self.ecs);
         let mut potions = ItemUseSystem {};
         potions.run_now(&self.ecs);
+        let mut throw_items = ItemThrowSystem {};
+        throw_items.run_now(&self.ecs);
+        let mut teleport = TeleportSystem {};
+        teleport.run_now(&self.ecs);
         let mut drop_items = ItemDropSystem {};
         drop_items.run_now(&self.ecs);
         let mut item_remove = ItemRemoveSystem {};
         item_remove.run_now(&self.ecs);
+        let mut encumbrance = EncumbranceSystem {};
+        encumbrance.run_now(&self.ecs);
         let mut hunger = HungerSystem {};
         hunger.run_now(&self.ecs);
+        let mut mana = ManaSystem {};
+        mana.run_now(&self.ecs);
+        let mut spoilage = SpoilageSystem {};
+        spoilage.run_now(&self.ecs);
         let mut particles = ParticleSpawnSystem {};
         particles.run_now(&self.ecs);
 
@@ -155,24 +221,46 @@ impl GameState for State {
                 self.ecs.maintain();
                 newrunstate = match *self.ecs.fetch::<RunState>() {
                     RunState::MagicMapReveal { .. } => RunState::MagicMapReveal { row: 0 },
+                    RunState::TeleportLevel { depth } => RunState::TeleportLevel { depth },
                     _ => RunState::MonsterTurn,
                 };
             }
             RunState::MonsterTurn => {
                 self.run_systems();
                 self.ecs.maintain();
-                newrunstate = RunState::AwaitingInput;
+                newrunstate = if encumbrance_system::player_falls_behind(&mut self.ecs) {
+                    RunState::MonsterTurn
+                } else {
+                    RunState::AwaitingInput
+                };
             }
             RunState::ShowInventory => {
                 let result = gui::show_inventory(self, ctx);
                 match result.0 {
                     gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
                     gui::ItemMenuResult::NoResponse => (),
+                    gui::ItemMenuResult::Selected
+                        if self
+                            .ecs
+                            .read_storage::<Spellbook>()
+                            .get(result.1.unwrap())
+                            .is_some() =>
+                    {
+                        newrunstate = player::learn_spell(&mut self.ecs, result.1.unwrap());
+                    }
                     gui::ItemMenuResult::Selected => {
                         let item_entity = result.1.unwrap();
                         let is_ranged = self.ecs.read_storage::<Ranged>();
                         let is_item_ranged = is_ranged.get(item_entity);
-                        if let Some(is_item_ranged) = is_item_ranged {
+                        let identifies = self.ecs.read_storage::<IdentifiesItem>();
+                        let repairs = self.ecs.read_storage::<RepairsItem>();
+                        if identifies.get(item_entity).is_some() {
+                            newrunstate = RunState::ShowIdentify {
+                                scroll: item_entity,
+                            };
+                        } else if repairs.get(item_entity).is_some() {
+                            newrunstate = RunState::ShowRepair { kit: item_entity };
+                        } else if let Some(is_item_ranged) = is_item_ranged {
                             newrunstate = RunState::ShowTargeting {
                                 range: is_item_ranged.range,
                                 item: item_entity,
@@ -199,7 +287,7 @@ impl GameState for State {
                     gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
                     gui::ItemMenuResult::NoResponse => (),
                     gui::ItemMenuResult::Selected => {
-                        let item_entity = result.1.unwrap();
+                        let item_entity = spawner::split_stack(&mut self.ecs, result.1.unwrap());
                         let mut intent = self.ecs.write_storage::<WantsToDropItem>();
                         intent
                             .insert(
@@ -249,6 +337,136 @@ impl GameState for State {
                     }
                 }
             }
+            RunState::ShowThrowItem => {
+                let result = gui::throw_item_menu(self, ctx);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => (),
+                    gui::ItemMenuResult::Selected => {
+                        newrunstate = RunState::ShowThrowTargeting {
+                            item: result.1.unwrap(),
+                        }
+                    }
+                }
+            }
+            RunState::ShowThrowTargeting { item } => {
+                let result = gui::ranged_target(self, ctx, throw_system::THROW_RANGE);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => (),
+                    gui::ItemMenuResult::Selected => {
+                        let item = spawner::split_stack(&mut self.ecs, item);
+                        let mut intent = self.ecs.write_storage::<WantsToThrowItem>();
+                        intent
+                            .insert(
+                                *self.ecs.fetch::<Entity>(),
+                                WantsToThrowItem {
+                                    item,
+                                    target: result.1.unwrap(),
+                                },
+                            )
+                            .expect("Unable to insert intent");
+                        newrunstate = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::ShowVendor { vendor, mode } => {
+                let result = gui::show_vendor(self, ctx, vendor, mode);
+                match result.0 {
+                    gui::VendorResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::VendorResult::NoResponse => (),
+                    gui::VendorResult::Buy => {
+                        player::buy_item(&mut self.ecs, vendor, result.1.unwrap())
+                    }
+                    gui::VendorResult::Sell => {
+                        player::sell_item(&mut self.ecs, vendor, result.1.unwrap())
+                    }
+                    gui::VendorResult::BuyMode => {
+                        newrunstate = RunState::ShowVendor {
+                            vendor,
+                            mode: gui::VendorMode::Buy,
+                        }
+                    }
+                    gui::VendorResult::SellMode => {
+                        newrunstate = RunState::ShowVendor {
+                            vendor,
+                            mode: gui::VendorMode::Sell,
+                        }
+                    }
+                }
+            }
+            RunState::ShowIdentify { scroll } => {
+                let result = gui::identify_item_menu(self, ctx, scroll);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => (),
+                    gui::ItemMenuResult::Selected => {
+                        player::identify_item(&mut self.ecs, scroll, result.1.unwrap());
+                        newrunstate = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::ShowRepair { kit } => {
+                let result = gui::repair_item_menu(self, ctx, kit);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => (),
+                    gui::ItemMenuResult::Selected => {
+                        player::repair_item(&mut self.ecs, kit, result.1.unwrap());
+                        newrunstate = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::ShowContainer { container } => {
+                let result = gui::show_container(self, ctx, container);
+                match result.0 {
+                    gui::LootResult::Cancel => newrunstate = RunState::PlayerTurn,
+                    gui::LootResult::NoResponse => (),
+                    gui::LootResult::TakeOne => {
+                        player::take_from_container(&mut self.ecs, container, result.1.unwrap());
+                    }
+                    gui::LootResult::TakeAll => {
+                        player::take_all_from_container(&mut self.ecs, container);
+                        newrunstate = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::ShowPickup => {
+                let result = gui::pickup_item_menu(self, ctx);
+                match result.0 {
+                    gui::LootResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::LootResult::NoResponse => (),
+                    gui::LootResult::TakeOne => {
+                        player::pick_up(&self.ecs, &[result.1.unwrap()]);
+                        newrunstate = RunState::PlayerTurn;
+                    }
+                    gui::LootResult::TakeAll => {
+                        player::pick_up(&self.ecs, &player::items_here(&self.ecs));
+                        newrunstate = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::ShowCastSpell => {
+                let result = gui::cast_spell_menu(self, ctx);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => (),
+                    gui::ItemMenuResult::Selected => {
+                        newrunstate = player::cast_spell(&mut self.ecs, result.1.unwrap());
+                    }
+                }
+            }
+            RunState::ShowAutoPickup => {
+                let result = gui::auto_pickup_menu(self, ctx);
+                match result.0 {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::ItemMenuResult::NoResponse => (),
+                    gui::ItemMenuResult::Selected => {
+                        let mut settings = self.ecs.write_resource::<AutoPickup>();
+                        settings.toggle(result.1.unwrap());
+                    }
+                }
+            }
             RunState::MainMenu { .. } => {
                 let result = gui::main_menu(self, ctx);
                 match result {
@@ -292,6 +510,16 @@ impl GameState for State {
                 self.mapgen_next_state = Some(RunState::PreRun);
                 newrunstate = RunState::MapGeneration;
             }
+            RunState::TeleportLevel { depth } => {
+                self.goto_level(depth);
+                let mut gamelog = self.ecs.fetch_mut::<gamelog::GameLog>();
+                gamelog.entries.push(format!(
+                    "You are torn from the level and flung to depth {}!",
+                    depth
+                ));
+                self.mapgen_next_state = Some(RunState::PreRun);
+                newrunstate = RunState::MapGeneration;
+            }
             RunState::MagicMapReveal { row } => {
                 let mut map = self.ecs.fetch_mut::<Map>();
                 for x in 0..map.width {
@@ -321,6 +549,7 @@ impl State {
         let backpack = self.ecs.read_storage::<InBackpack>();
         let player_entity = self.ecs.fetch::<Entity>();
         let equipped = self.ecs.read_storage::<Equipped>();
+        let known_spells = self.ecs.read_storage::<KnownSpell>();
 
         let mut to_delete: Vec<Entity> = Vec::new();
         for entity in entities.join() {
@@ -343,6 +572,13 @@ impl State {
                 }
             }
 
+            // Don't make the player forget their spells
+            if let Some(spell) = known_spells.get(entity) {
+                if spell.owner == *player_entity {
+                    continue;
+                }
+            }
+
             to_delete.push(entity);
         }
 
@@ -350,21 +586,12 @@ impl State {
     }
 
     fn goto_next_level(&mut self) {
-        // Delete entities that aren't the player or his/her equipment
-        let to_delete = self.entities_to_remove_on_level_change();
-        for target in to_delete {
-            self.ecs
-                .delete_entity(target)
-                .expect("Unable to delete entity");
-        }
-
-        // Build a new map and place the player
         let current_depth;
         {
             let worldmap_resource = self.ecs.write_resource::<Map>();
             current_depth = worldmap_resource.depth;
         }
-        self.generate_world_map(current_depth + 1);
+        self.goto_level(current_depth + 1);
 
         // Notify the player and give them some health
         let player_entity = self.ecs.fetch::<Entity>();
@@ -379,6 +606,21 @@ impl State {
         }
     }
 
+    /// Replaces the current level with a new one at the given depth, keeping only the
+    /// player and what they carry
+    fn goto_level(&mut self, new_depth: i32) {
+        // Delete entities that aren't the player or his/her equipment
+        let to_delete = self.entities_to_remove_on_level_change();
+        for target in to_delete {
+            self.ecs
+                .delete_entity(target)
+                .expect("Unable to delete entity");
+        }
+
+        // Build a new map and place the player
+        self.generate_world_map(new_depth);
+    }
+
     fn game_over_cleanup(&mut self) {
         // Delete everything
         let mut to_delete = Vec::new();
@@ -389,6 +631,14 @@ impl State {
             self.ecs.delete_entity(*del).expect("Deletion failed");
         }
 
+        // It's a fresh run, so every unique is back in play and magic items look different
+        self.ecs.insert(UniqueTracker::default());
+        let identification = {
+            let mut rng = self.ecs.write_resource::<rltk::RandomNumberGenerator>();
+            spawner::item_identification(&mut rng)
+        };
+        self.ecs.insert(identification);
+
         // Spawn a new player
         {
             let player_entity = spawner::player(&mut self.ecs, 0, 0);
@@ -461,24 +711,44 @@ fn main() -> rltk::BError {
         mapgen_history: Vec::new(),
         mapgen_index: 0,
         mapgen_timer: 0.0,
+        menu_page: 0,
     };
     gs.ecs.register::<Position>();
     gs.ecs.register::<Renderable>();
     gs.ecs.register::<Player>();
     gs.ecs.register::<Viewshed>();
     gs.ecs.register::<Monster>();
+    gs.ecs.register::<Faction>();
+    gs.ecs.register::<Behavior>();
+    gs.ecs.register::<KeepsDistance>();
     gs.ecs.register::<Name>();
     gs.ecs.register::<BlocksTile>();
     gs.ecs.register::<BlocksVisibility>();
     gs.ecs.register::<Door>();
+    gs.ecs.register::<OpensDoors>();
+    gs.ecs.register::<UsesItems>();
+    gs.ecs.register::<Boss>();
+    gs.ecs.register::<BashesDoors>();
+    gs.ecs.register::<WantsToOpenDoor>();
+    gs.ecs.register::<WantsToCloseDoor>();
+    gs.ecs.register::<WantsToBashDoor>();
     gs.ecs.register::<CombatStats>();
+    gs.ecs.register::<NaturalAttacks>();
     gs.ecs.register::<WantsToMelee>();
     gs.ecs.register::<SufferDamage>();
     gs.ecs.register::<Item>();
+    gs.ecs.register::<PackMember>();
+    gs.ecs.register::<Routed>();
+    gs.ecs.register::<Ability>();
     gs.ecs.register::<InBackpack>();
     gs.ecs.register::<WantsToPickupItem>();
     gs.ecs.register::<WantsToUseItem>();
     gs.ecs.register::<WantsToDropItem>();
+    gs.ecs.register::<WantsToThrowItem>();
+    gs.ecs.register::<Teleports>();
+    gs.ecs.register::<WantsToTeleport>();
+    gs.ecs.register::<LightSource>();
+    gs.ecs.register::<Fuel>();
     gs.ecs.register::<WantsToRemoveItem>();
     gs.ecs.register::<Consumable>();
     gs.ecs.register::<ProvidesHealing>();
@@ -495,6 +765,33 @@ fn main() -> rltk::BError {
     gs.ecs.register::<ParticleLifetime>();
     gs.ecs.register::<HungerClock>();
     gs.ecs.register::<ProvidesFood>();
+    gs.ecs.register::<Perishable>();
+    gs.ecs.register::<Sickness>();
+    gs.ecs.register::<IdentifiesItem>();
+    gs.ecs.register::<Cursed>();
+    gs.ecs.register::<CurseKnown>();
+    gs.ecs.register::<RemovesCurse>();
+    gs.ecs.register::<Charges>();
+    gs.ecs.register::<Recharges>();
+    gs.ecs.register::<Stackable>();
+    gs.ecs.register::<Throwable>();
+    gs.ecs.register::<Potion>();
+    gs.ecs.register::<Weight>();
+    gs.ecs.register::<Attributes>();
+    gs.ecs.register::<Mana>();
+    gs.ecs.register::<KnownSpell>();
+    gs.ecs.register::<Spellbook>();
+    gs.ecs.register::<Encumbrance>();
+    gs.ecs.register::<Durability>();
+    gs.ecs.register::<RepairsItem>();
+    gs.ecs.register::<MagicItem>();
+    gs.ecs.register::<ElementalDamage>();
+    gs.ecs.register::<AttributeBonus>();
+    gs.ecs.register::<Value>();
+    gs.ecs.register::<Gold>();
+    gs.ecs.register::<Purse>();
+    gs.ecs.register::<Vendor>();
+    gs.ecs.register::<Container>();
     gs.ecs.register::<MagicMapper>();
     gs.ecs.register::<Hidden>();
     gs.ecs.register::<EntryTrigger>();
@@ -516,6 +813,11 @@ fn main() -> rltk::BError {
         entries: vec!["Welcome to Rusty Roguelike".to_string()],
     });
     gs.ecs.insert(particle_system::ParticleBuilder::new());
+    gs.ecs.insert(navigation_system::NavigationMaps::default());
+    gs.ecs.insert(noise_system::NoiseBuilder::new());
+    gs.ecs.insert(UniqueTracker::default());
+    gs.ecs.insert(AutoPickup::default());
+    gs.ecs.insert(spawner::item_identification(&mut rng));
     gs.ecs.insert(rex_assets::RexAssets::new());
 
     gs.generate_world_map(1);
@@ -0,0 +1,30 @@
+use super::{Attributes, Mana, RunState};
+use specs::prelude::*;
+
+/// Slowly refills everyone's mana, at a pace set by their intelligence
+pub struct ManaSystem {}
+
+impl<'a> System<'a> for ManaSystem {
+    type SystemData = (
+        ReadExpect<'a, RunState>,
+        ReadStorage<'a, Attributes>,
+        WriteStorage<'a, Mana>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (runstate, attributes, mut mana) = data;
+
+        if *runstate != RunState::PlayerTurn {
+            return;
+        }
+
+        for (attributes, mana) in (&attributes, &mut mana).join() {
+            mana.max = attributes.max_mana();
+            mana.recovery += 1;
+            if mana.recovery >= attributes.mana_regen_turns() {
+                mana.recovery = 0;
+                mana.current = i32::min(mana.max, mana.current + 1);
+            }
+        }
+    }
+}
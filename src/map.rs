@@ -1,4 +1,4 @@
-use rltk::{Algorithm2D, BaseMap, Point};
+use rltk::{Algorithm2D, BaseMap, Point, RGB};
 use serde::{Deserialize, Serialize};
 use specs::prelude::*;
 use std::collections::HashSet;
@@ -10,6 +10,9 @@ pub enum TileType {
     DownStairs,
 }
 
+/// The dimmest light the player can still see by
+const LIT_THRESHOLD: f32 = 0.15;
+
 #[derive(Default, Serialize, Deserialize, Clone)]
 pub struct Map {
     pub tiles: Vec<TileType>,
@@ -21,6 +24,7 @@ pub struct Map {
     pub depth: i32,
     pub bloodstains: HashSet<usize>,
     pub view_blocked: HashSet<usize>,
+    pub light: Vec<RGB>,
 
     #[serde(skip_serializing)]
     #[serde(skip_deserializing)]
@@ -40,6 +44,12 @@ impl Map {
         !self.blocked[idx]
     }
 
+    /// Whether there's enough light on a tile for the player to make it out
+    pub fn is_lit(&self, idx: usize) -> bool {
+        let light = self.light[idx];
+        f32::max(light.r, f32::max(light.g, light.b)) >= LIT_THRESHOLD
+    }
+
     pub fn populate_blocked(&mut self) {
         for (i, tile) in self.tiles.iter_mut().enumerate() {
             self.blocked[i] = *tile == TileType::Wall;
@@ -66,6 +76,7 @@ impl Map {
             depth: new_depth,
             bloodstains: HashSet::new(),
             view_blocked: HashSet::new(),
+            light: vec![RGB::from_f32(1.0, 1.0, 1.0); map_tile_count],
         }
     }
 }
@@ -258,12 +258,32 @@ fn random_shape_builder(rng: &mut RandomNumberGenerator, builder: &mut BuilderCh
     builder.with(DistantExit::new());
 }
 
+/// Handcrafted boss lairs that replace the random level at their depth
+fn boss_lair(depth: i32) -> Option<prefab_builder::prefab_levels::PrefabLevel> {
+    match depth {
+        4 => Some(prefab_builder::prefab_levels::GOBLIN_KING_LAIR),
+        8 => Some(prefab_builder::prefab_levels::ORC_WARLORD_LAIR),
+        _ => None,
+    }
+}
+
+/// Whether a boss waits at this depth
+pub fn is_boss_lair(depth: i32) -> bool {
+    boss_lair(depth).is_some()
+}
+
 pub fn random_builder(
     new_depth: i32,
     rng: &mut RandomNumberGenerator,
     width: i32,
     height: i32,
 ) -> BuilderChain {
+    if let Some(lair) = boss_lair(new_depth) {
+        let mut builder = BuilderChain::new(new_depth, lair.width as i32, lair.height as i32);
+        builder.start_with(PrefabBuilder::constant(lair));
+        return builder;
+    }
+
     let mut builder = BuilderChain::new(new_depth, width, height);
     let type_roll = rng.roll_dice(1, 2);
     match type_roll {
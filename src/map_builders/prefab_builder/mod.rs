@@ -96,6 +96,40 @@ impl PrefabBuilder {
                 build_data.map.tiles[idx] = TileType::Floor;
                 build_data.spawn_list.push((idx, "Rations".to_string()));
             }
+            's' => {
+                build_data.map.tiles[idx] = TileType::Floor;
+                build_data
+                    .spawn_list
+                    .push((idx, "Goblin Shaman".to_string()));
+            }
+            '+' => {
+                build_data.map.tiles[idx] = TileType::Floor;
+                build_data.spawn_list.push((idx, "Door".to_string()));
+            }
+            'K' => {
+                build_data.map.tiles[idx] = TileType::Floor;
+                build_data
+                    .spawn_list
+                    .push((idx, "Grubnak the Goblin King".to_string()));
+            }
+            'W' => {
+                build_data.map.tiles[idx] = TileType::Floor;
+                build_data
+                    .spawn_list
+                    .push((idx, "Ugra the Orc Warlord".to_string()));
+            }
+            'V' => {
+                build_data.map.tiles[idx] = TileType::Floor;
+                build_data.spawn_list.push((idx, "Vendor".to_string()));
+            }
+            'C' => {
+                build_data.map.tiles[idx] = TileType::Floor;
+                build_data.spawn_list.push((idx, "Chest".to_string()));
+            }
+            'B' => {
+                build_data.map.tiles[idx] = TileType::Floor;
+                build_data.spawn_list.push((idx, "Barrel".to_string()));
+            }
             '!' => {
                 build_data.map.tiles[idx] = TileType::Floor;
                 build_data
@@ -227,7 +261,14 @@ impl PrefabBuilder {
         }
 
         // Note that this is a place-holder and will be moved out of this function
-        let master_vault_list = vec![TOTALLY_NOT_A_TRAP, CHECKERBOARD, SILLY_SMILE];
+        let master_vault_list = vec![
+            TOTALLY_NOT_A_TRAP,
+            CHECKERBOARD,
+            SILLY_SMILE,
+            SHOP,
+            TREASURE_ROOM,
+            STOREROOM,
+        ];
 
         // Filter the vault list down to ones that are applicable to the current depth
         let mut possible_vaults: Vec<&PrefabRoom> = master_vault_list
@@ -56,3 +56,62 @@ const LEVEL_MAP: &str = "
 # %^##  ^   ###     ############### ########      #####       ##################
 ################################################################################
 ";
+
+pub const GOBLIN_KING_LAIR: PrefabLevel = PrefabLevel {
+    template: GOBLIN_KING_LAIR_MAP,
+    width: 50,
+    height: 20,
+};
+
+const GOBLIN_KING_LAIR_MAP: &str = "
+##################################################
+#@     ######################        #############
+#      ######################   g    ###   ^   ###
+#      #######        #######        ###       ###
+###  #########   !    ####### g      ###   !   ###
+###  #########        #######             ########
+###  ############ ############        ### ########
+###  ############ ####################### ########
+###                                       ########
+###### ################## ####################  ##
+#####   ####     ####       ####   ^^^^^^^^^^^  ##
+#####   +    g   +      s   +                   ##
+#####   ####     ####       ####   ^^^^^^^^^^^  ##
+######################### ################### ####
+######################### ####            ### ####
+#####################       #  g       g  ###+####
+#####################   %   +                   ##
+#####################       #     K        >    ##
+########################################%    !  ##
+##################################################
+";
+
+pub const ORC_WARLORD_LAIR: PrefabLevel = PrefabLevel {
+    template: ORC_WARLORD_LAIR_MAP,
+    width: 60,
+    height: 21,
+};
+
+const ORC_WARLORD_LAIR_MAP: &str = "
+############################################################
+#######################          ###########################
+###      ##############   o    o  ##########################
+### @    ##############           ######        ############
+###      +                 %            +   o    #   !  ####
+###      ##############           ######        #       ####
+######  ###############   o       ######        #  o    ####
+######  ###########################################+########
+######  ##########                    ##########       #####
+######                ^       ^         ########   o   #####
+##########  ######    ^   o   ^      o  ########       #####
+##########  ######    ^       ^         ######### ##########
+##########  ######                    ########### ##########
+##########  ################ ##################### #########
+#########    ###############+#####                 #########
+#########  !  ############       ####  ########### #########
+#########    #############   o   ####  ###     ### #########
+##############################   ########## W  >### ########
+##############################   ##########       +   ######
+########################################### ! %  ###########
+############################################################
+";
@@ -56,3 +56,53 @@ const CHECKERBOARD_MAP: &str = "
  ^# # 
       
 ";
+
+pub const SHOP: PrefabRoom = PrefabRoom {
+    template: SHOP_MAP,
+    width: 7,
+    height: 6,
+    first_depth: 1,
+    last_depth: 100,
+};
+
+const SHOP_MAP: &str = "
+       
+ ##### 
+ # V # 
+ #   # 
+ ## ## 
+       
+";
+
+pub const TREASURE_ROOM: PrefabRoom = PrefabRoom {
+    template: TREASURE_ROOM_MAP,
+    width: 7,
+    height: 6,
+    first_depth: 2,
+    last_depth: 100,
+};
+
+const TREASURE_ROOM_MAP: &str = "
+       
+ ##+## 
+ #^ C# 
+ #C ^# 
+ ##### 
+       
+";
+
+pub const STOREROOM: PrefabRoom = PrefabRoom {
+    template: STOREROOM_MAP,
+    width: 7,
+    height: 5,
+    first_depth: 0,
+    last_depth: 100,
+};
+
+const STOREROOM_MAP: &str = "
+       
+ BB B  
+ B   B 
+  B BB 
+       
+";
@@ -1,12 +1,21 @@
 use super::{
-    gamelog::GameLog, particle_system::ParticleBuilder, CombatStats, DefenseBonus, Equipped,
-    HungerClock, HungerState, MeleePowerBonus, Name, Position, SufferDamage, WantsToMelee,
+    gamelog::GameLog,
+    noise_system::{NoiseBuilder, COMBAT_VOLUME},
+    particle_system::ParticleBuilder,
+    AttackEffect, Behavior, CombatStats, Confusion, DefenseBonus, Durability, ElementalDamage,
+    Encumbrance, Equipped, HungerClock, HungerState, MeleePowerBonus, Name, NaturalAttacks,
+    Position, SufferDamage, WantsToMelee,
 };
 use specs::prelude::*;
+use std::collections::HashSet;
+
+/// Extra damage for hitting something that hasn't noticed its attacker
+const SNEAK_ATTACK_BONUS: i32 = 4;
 
 pub struct MeleeCombatSystem {}
 
 impl<'a> System<'a> for MeleeCombatSystem {
+    #[allow(clippy::type_complexity)]
     type SystemData = (
         Entities<'a>,
         WriteExpect<'a, GameLog>,
@@ -20,6 +29,15 @@ impl<'a> System<'a> for MeleeCombatSystem {
         WriteExpect<'a, ParticleBuilder>,
         ReadStorage<'a, Position>,
         ReadStorage<'a, HungerClock>,
+        ReadStorage<'a, NaturalAttacks>,
+        WriteStorage<'a, Confusion>,
+        WriteExpect<'a, rltk::RandomNumberGenerator>,
+        ReadStorage<'a, Behavior>,
+        WriteExpect<'a, NoiseBuilder>,
+        ReadStorage<'a, Encumbrance>,
+        WriteStorage<'a, Durability>,
+        ReadExpect<'a, Entity>,
+        ReadStorage<'a, ElementalDamage>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -36,31 +54,57 @@ impl<'a> System<'a> for MeleeCombatSystem {
             mut particle_builder,
             positions,
             hunger_clock,
+            natural_attacks,
+            mut confused,
+            mut rng,
+            behaviors,
+            mut noise_builder,
+            encumbrance,
+            mut durability,
+            player_entity,
+            elemental_damage,
         ) = data;
 
+        // Gear that took part in a blow, once per blow
+        let mut worn: HashSet<Entity> = HashSet::new();
+
         for (entity, wants_melee, name, stats) in
             (&entities, &wants_melee, &names, &combat_stats).join()
         {
             if stats.hp > 0 {
                 let mut offensive_bonus = 0;
-                for (_item_entity, power_bonus, equipped_by) in
+                let mut weapons: Vec<Entity> = Vec::new();
+                for (item_entity, power_bonus, equipped_by) in
                     (&entities, &melee_power_bonuses, &equipped).join()
                 {
                     if equipped_by.owner == entity {
-                        offensive_bonus += power_bonus.power;
+                        offensive_bonus += durability
+                            .get(item_entity)
+                            .map_or(power_bonus.power, |d| d.effective_bonus(power_bonus.power));
+                        weapons.push(item_entity);
                     }
                 }
+                let elemental: Vec<&ElementalDamage> = weapons
+                    .iter()
+                    .filter_map(|weapon| elemental_damage.get(*weapon))
+                    .collect();
 
                 let target_stats = combat_stats.get(wants_melee.target).unwrap();
                 if target_stats.hp > 0 {
                     let target_name = names.get(wants_melee.target).unwrap();
 
                     let mut defensive_bonus = 0;
-                    for (_item_entity, defense_bonus, equipped_by) in
+                    let mut armor: Vec<Entity> = Vec::new();
+                    for (item_entity, defense_bonus, equipped_by) in
                         (&entities, &defense_bonuses, &equipped).join()
                     {
                         if equipped_by.owner == wants_melee.target {
-                            defensive_bonus += defense_bonus.defense;
+                            defensive_bonus += durability
+                                .get(item_entity)
+                                .map_or(defense_bonus.defense, |d| {
+                                    d.effective_bonus(defense_bonus.defense)
+                                });
+                            armor.push(item_entity);
                         }
                     }
 
@@ -71,8 +115,24 @@ impl<'a> System<'a> for MeleeCombatSystem {
                         }
                     }
 
+                    if let Some(load) = encumbrance.get(entity) {
+                        offensive_bonus -= load.tier.melee_penalty();
+                    }
+
+                    if behaviors
+                        .get(wants_melee.target)
+                        .is_some_and(|behavior| behavior.state.is_unaware())
+                    {
+                        offensive_bonus += SNEAK_ATTACK_BONUS;
+                        log.entries.push(format!(
+                            "{} catches {} unawares!",
+                            &name.name, &target_name.name
+                        ));
+                    }
+
                     let pos = positions.get(wants_melee.target);
                     if let Some(pos) = pos {
+                        noise_builder.request(pos.x, pos.y, COMBAT_VOLUME);
                         particle_builder.request(
                             pos.x,
                             pos.y,
@@ -83,6 +143,58 @@ impl<'a> System<'a> for MeleeCombatSystem {
                         );
                     }
 
+                    // Creatures with natural attacks resolve each of them in turn
+                    if let Some(natural) = natural_attacks.get(entity) {
+                        for attack in natural.attacks.iter() {
+                            let hit_roll = rng.roll_dice(1, 20);
+                            if hit_roll == 1 || hit_roll + attack.hit_bonus < 10 {
+                                log.entries.push(format!(
+                                    "{}'s {} misses {}.",
+                                    &name.name, attack.name, &target_name.name
+                                ));
+                                continue;
+                            }
+                            worn.extend(armor.iter());
+
+                            let damage = i32::max(
+                                0,
+                                (attack.damage + offensive_bonus)
+                                    - (target_stats.defense + defensive_bonus),
+                            );
+                            if damage == 0 {
+                                log.entries.push(format!(
+                                    "{}'s {} fails to hurt {}.",
+                                    &name.name, attack.name, &target_name.name
+                                ));
+                                continue;
+                            }
+
+                            log.entries.push(format!(
+                                "{}'s {} hits {}, for {} hp.",
+                                &name.name, attack.name, &target_name.name, damage
+                            ));
+                            SufferDamage::new_damage(
+                                &mut inflict_damage,
+                                wants_melee.target,
+                                damage,
+                            );
+
+                            match attack.effect {
+                                None => (),
+                                Some(AttackEffect::Confusion { turns }) => {
+                                    confused
+                                        .insert(wants_melee.target, Confusion { turns })
+                                        .expect("Unable to insert status");
+                                    log.entries
+                                        .push(format!("{} is confused!", &target_name.name));
+                                }
+                            }
+                        }
+                        continue;
+                    }
+
+                    worn.extend(weapons.iter());
+                    worn.extend(armor.iter());
                     let damage = i32::max(
                         0,
                         (stats.power + offensive_bonus) - (target_stats.defense + defensive_bonus),
@@ -100,6 +212,45 @@ impl<'a> System<'a> for MeleeCombatSystem {
                         ));
                         SufferDamage::new_damage(&mut inflict_damage, wants_melee.target, damage);
                     }
+
+                    // Enchanted weapons hurt whether or not the blow got through armor
+                    for extra in elemental.iter() {
+                        log.entries.push(format!(
+                            "{} is {}, for {} hp.",
+                            &target_name.name,
+                            extra.element.verb(),
+                            extra.damage
+                        ));
+                        SufferDamage::new_damage(
+                            &mut inflict_damage,
+                            wants_melee.target,
+                            extra.damage,
+                        );
+                    }
+                }
+            }
+        }
+
+        // Every fight takes its toll on the weapons and armor involved, once a round however
+        // many blows land
+        for item in worn.iter() {
+            if let Some(condition) = durability.get_mut(*item) {
+                condition.current -= 1;
+                if condition.current <= 0 {
+                    let item_name = names.get(*item).map_or("gear", |n| n.name.as_str());
+                    match equipped.get(*item).map(|eq| eq.owner) {
+                        Some(owner) if owner == *player_entity => {
+                            log.entries.push(format!("Your {} breaks!", item_name))
+                        }
+                        Some(owner) => {
+                            if let Some(owner_name) = names.get(owner) {
+                                log.entries
+                                    .push(format!("{}'s {} breaks!", owner_name.name, item_name));
+                            }
+                        }
+                        None => (),
+                    }
+                    entities.delete(*item).expect("Unable to delete");
                 }
             }
         }
@@ -1,9 +1,16 @@
 use super::{
-    particle_system::ParticleBuilder, Confusion, EntityMoved, Map, Monster, Position, RunState,
-    Viewshed, WantsToMelee,
+    faction_table::{faction_reaction, Reaction},
+    navigation_system::{GoalMaps, NavigationMaps},
+    particle_system::ParticleBuilder,
+    Ability, AiState, BashesDoors, Behavior, CombatStats, Confusion, Door, EntityMoved, Faction,
+    KeepsDistance, Map, Monster, OpensDoors, PackMember, Position, Ranged, Routed, RunState,
+    Viewshed, WantsToBashDoor, WantsToMelee, WantsToOpenDoor, WantsToPickupItem, WantsToUseItem,
 };
-use rltk::Point;
+use rltk::{BaseMap, Point, RandomNumberGenerator};
 use specs::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+pub const SEARCH_TURNS: i32 = 8;
 
 pub struct MonsterAI {}
 
@@ -11,8 +18,6 @@ impl<'a> System<'a> for MonsterAI {
     #[allow(clippy::type_complexity)]
     type SystemData = (
         WriteExpect<'a, Map>,
-        ReadExpect<'a, Point>,
-        ReadExpect<'a, Entity>,
         ReadExpect<'a, RunState>,
         Entities<'a>,
         WriteStorage<'a, Viewshed>,
@@ -22,13 +27,31 @@ impl<'a> System<'a> for MonsterAI {
         WriteStorage<'a, Confusion>,
         WriteExpect<'a, ParticleBuilder>,
         WriteStorage<'a, EntityMoved>,
+        ReadStorage<'a, Faction>,
+        ReadStorage<'a, CombatStats>,
+        WriteStorage<'a, Behavior>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteStorage<'a, Ability>,
+        ReadStorage<'a, Ranged>,
+        ReadStorage<'a, KeepsDistance>,
+        WriteStorage<'a, WantsToUseItem>,
+        ReadExpect<'a, NavigationMaps>,
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, Point>,
+        (
+            ReadStorage<'a, Door>,
+            ReadStorage<'a, OpensDoors>,
+            ReadStorage<'a, BashesDoors>,
+            WriteStorage<'a, WantsToOpenDoor>,
+            WriteStorage<'a, WantsToBashDoor>,
+        ),
+        (ReadStorage<'a, PackMember>, ReadStorage<'a, Routed>),
+        ReadStorage<'a, WantsToPickupItem>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
             mut map,
-            player_pos,
-            player_entity,
             runstate,
             entities,
             mut viewshed,
@@ -38,14 +61,86 @@ impl<'a> System<'a> for MonsterAI {
             mut confused,
             mut particle_builder,
             mut entity_moved,
+            factions,
+            combat_stats,
+            mut behaviors,
+            mut rng,
+            mut abilities,
+            ranged,
+            keeps_distance,
+            mut wants_use,
+            navigation,
+            player_entity,
+            player_pos,
+            (doors, opens_doors, bashes_doors, mut wants_open, mut wants_bash),
+            (pack_members, routed),
+            wants_pickup,
         ) = data;
 
         if *runstate != RunState::MonsterTurn {
             return;
         }
 
-        for (entity, mut viewshed, _monster, mut pos) in
-            (&entities, &mut viewshed, &monster, &mut position).join()
+        // The player doesn't block the map, but nobody gets to walk onto them either
+        let player_idx = map.xy_idx(player_pos.x, player_pos.y);
+        map.blocked[player_idx] = true;
+
+        // Cool down everyone's abilities, and note which are ready to use and their reach
+        let mut ready_abilities: HashMap<Entity, Vec<(Entity, i32)>> = HashMap::new();
+        let mut ability_reach: HashMap<Entity, i32> = HashMap::new();
+        for (ability_entity, ability, range) in (&entities, &mut abilities, &ranged).join() {
+            if ability.cooldown_remaining > 0 {
+                ability.cooldown_remaining -= 1;
+            }
+            if ability.cooldown_remaining == 0 {
+                ready_abilities
+                    .entry(ability.owner)
+                    .or_default()
+                    .push((ability_entity, range.range));
+            }
+            let reach = ability_reach.entry(ability.owner).or_insert(0);
+            *reach = i32::max(*reach, range.range);
+        }
+
+        // Closed doors block the map, but some monsters know how to get through them
+        let closed_doors: HashMap<usize, Entity> = (&entities, &doors, &position)
+            .join()
+            .filter(|(_entity, door, _pos)| !door.open)
+            .map(|(entity, _door, pos)| (map.xy_idx(pos.x, pos.y), entity))
+            .collect();
+
+        // Packs are known by their leader, who stays where the band gathers
+        let mut leader_positions: HashMap<Entity, Point> = HashMap::new();
+        for member in pack_members.join() {
+            if let Some(pos) = position.get(member.leader) {
+                leader_positions.insert(member.leader, Point::new(pos.x, pos.y));
+            }
+        }
+        // Everywhere else monsters are heading shares a flow map, built on demand
+        let door_tiles: HashSet<usize> = (&doors, &position)
+            .join()
+            .map(|(_door, pos)| map.xy_idx(pos.x, pos.y))
+            .collect();
+        let mut goal_maps = GoalMaps::new(door_tiles);
+
+        let pack_of = |entity: Entity| -> Option<Entity> {
+            match pack_members.get(entity) {
+                Some(member) => Some(member.leader),
+                None if leader_positions.contains_key(&entity) => Some(entity),
+                None => None,
+            }
+        };
+        let mut pack_alerts: HashMap<Entity, Point> = HashMap::new();
+
+        for (entity, mut viewshed, _monster, mut pos, faction, behavior) in (
+            &entities,
+            &mut viewshed,
+            &monster,
+            &mut position,
+            &factions,
+            &mut behaviors,
+        )
+            .join()
         {
             let mut can_act = true;
             let is_confused = confused.get_mut(entity);
@@ -66,38 +161,316 @@ impl<'a> System<'a> for MonsterAI {
                 );
             }
 
-            if can_act {
-                let distance =
-                    rltk::DistanceAlg::Pythagoras.distance2d(Point::new(pos.x, pos.y), *player_pos);
-                if distance < 1.5 {
-                    wants_to_melee
-                        .insert(
-                            entity,
-                            WantsToMelee {
-                                target: *player_entity,
-                            },
-                        )
-                        .expect("Unable to insert attack");
-                } else if viewshed.visible_tiles.contains(&*player_pos) {
-                    let path = rltk::a_star_search(
-                        map.xy_idx(pos.x, pos.y) as i32,
-                        map.xy_idx(player_pos.x, player_pos.y) as i32,
-                        &mut *map,
-                    );
-                    if path.success && path.steps.len() > 1 {
-                        let mut idx = map.xy_idx(pos.x, pos.y);
-                        map.blocked[idx] = false;
-                        pos.x = path.steps[1] as i32 % map.width;
-                        pos.y = path.steps[1] as i32 / map.width;
-                        entity_moved
-                            .insert(entity, EntityMoved {})
-                            .expect("Unable to insert marker");
-                        idx = map.xy_idx(pos.x, pos.y);
-                        map.blocked[idx] = true;
-                        viewshed.dirty = true;
+            if !can_act {
+                continue;
+            }
+
+            // Using or picking up an item takes the whole turn
+            if wants_use.get(entity).is_some() || wants_pickup.get(entity).is_some() {
+                continue;
+            }
+
+            // Look around for anything we have feelings about
+            let my_pos = Point::new(pos.x, pos.y);
+            let mut target: Option<(Entity, Point, f32)> = None;
+            let mut threats: Vec<Point> = Vec::new();
+            let mut hostiles: Vec<Point> = Vec::new();
+            for tile in viewshed.visible_tiles.iter() {
+                let idx = map.xy_idx(tile.x, tile.y);
+                for other in map.tile_content[idx].iter() {
+                    if *other == entity || combat_stats.get(*other).is_none() {
+                        continue;
+                    }
+                    if let Some(other_faction) = factions.get(*other) {
+                        match faction_reaction(&faction.name, &other_faction.name) {
+                            Reaction::Ignore => (),
+                            Reaction::Flee => threats.push(*tile),
+                            Reaction::Attack => {
+                                hostiles.push(*tile);
+                                let distance =
+                                    rltk::DistanceAlg::Pythagoras.distance2d(my_pos, *tile);
+                                if target.is_none_or(|(_, _, best)| distance < best) {
+                                    target = Some((*other, *tile, distance));
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            // Monsters that haven't noticed anything yet need to pass a perception check, which
+            // is harder the further away things are and much harder while asleep
+            if behavior.state.is_unaware() {
+                let nearest = hostiles
+                    .iter()
+                    .chain(threats.iter())
+                    .map(|tile| rltk::DistanceAlg::Pythagoras.distance2d(my_pos, *tile))
+                    .fold(f32::MAX, f32::min);
+                if nearest < f32::MAX {
+                    let mut difficulty = 10 + nearest as i32;
+                    if behavior.state == AiState::Sleeping {
+                        difficulty += 5;
+                    }
+                    if rng.roll_dice(1, 20) + behavior.perception < difficulty {
+                        target = None;
+                        threats.clear();
+                        hostiles.clear();
+                    }
+                }
+            }
+
+            // Badly hurt monsters are scared of everything they would normally fight, as are
+            // pack members who have lost their leader
+            if let Some(stats) = combat_stats.get(entity) {
+                if stats.hp * 100 < stats.max_hp * behavior.flee_below_percent
+                    || routed.get(entity).is_some()
+                {
+                    threats.append(&mut hostiles);
+                }
+            }
+
+            // Sleeping monsters wake up alert when they notice something, which takes their turn
+            if behavior.state == AiState::Sleeping {
+                let noticed = target.map(|(_, tile, _)| tile).or(threats.first().copied());
+                if let Some(noticed) = noticed {
+                    behavior.state = AiState::Searching {
+                        x: noticed.x,
+                        y: noticed.y,
+                        turns: SEARCH_TURNS,
+                    };
+                }
+                continue;
+            }
+
+            behavior.state = if !threats.is_empty() {
+                AiState::Fleeing
+            } else if let Some((_, target_pos, _)) = target {
+                AiState::Chasing {
+                    x: target_pos.x,
+                    y: target_pos.y,
+                }
+            } else {
+                match behavior.state {
+                    AiState::Chasing { x, y } => AiState::Searching {
+                        x,
+                        y,
+                        turns: SEARCH_TURNS,
+                    },
+                    AiState::Fleeing => AiState::Idle,
+                    state => state,
+                }
+            };
+
+            // Whoever spots a target raises the alarm for the rest of the pack
+            let pack = pack_of(entity);
+            if let (Some(pack), AiState::Chasing { x, y }) = (pack, behavior.state) {
+                pack_alerts.insert(pack, Point::new(x, y));
+            }
+
+            let my_idx = map.xy_idx(pos.x, pos.y);
+            let mut destination: Option<usize> = None;
+            let uses_doors =
+                opens_doors.get(entity).is_some() || bashes_doors.get(entity).is_some();
+            if uses_doors {
+                for door_idx in closed_doors.keys() {
+                    map.blocked[*door_idx] = false;
+                }
+            }
+            match behavior.state {
+                AiState::Sleeping => (),
+                AiState::Idle => {
+                    let following = pack
+                        .filter(|leader| *leader != entity)
+                        .and_then(|leader| leader_positions.get(&leader));
+                    if let Some(leader_pos) = following {
+                        // Followers stay close to their leader rather than wandering off
+                        if rltk::DistanceAlg::Pythagoras.distance2d(my_pos, *leader_pos) > 2.0 {
+                            let leader_idx = map.xy_idx(leader_pos.x, leader_pos.y);
+                            destination = goal_maps.step_towards(&map, my_idx, leader_idx);
+                        }
+                    } else if behavior.wanders {
+                        if let Some(wander_to) = goal_maps.random_landmark(&map, &mut rng) {
+                            behavior.state = AiState::Wandering {
+                                destination: wander_to,
+                            };
+                            destination = goal_maps.step_towards(&map, my_idx, wander_to);
+                        }
+                    }
+                }
+                AiState::Wandering {
+                    destination: wander_to,
+                } => {
+                    destination = goal_maps.step_towards(&map, my_idx, wander_to);
+                    if destination.is_none() {
+                        // We either arrived or can't get there, so pick somewhere else next turn
+                        behavior.state = AiState::Idle;
+                    }
+                }
+                AiState::Chasing { .. } => {
+                    if let Some((target_entity, target_pos, distance)) = target {
+                        let target_idx = map.xy_idx(target_pos.x, target_pos.y);
+                        let ability = ready_abilities.get(&entity).and_then(|ready| {
+                            ready
+                                .iter()
+                                .find(|(_ability, range)| distance <= *range as f32)
+                        });
+                        let too_close = keeps_distance
+                            .get(entity)
+                            .is_some_and(|keep| distance < keep.range as f32);
+
+                        if let Some((ability_entity, _range)) = ability {
+                            // We can see them and they're in range, so let them have it
+                            wants_use
+                                .insert(
+                                    entity,
+                                    WantsToUseItem {
+                                        item: *ability_entity,
+                                        target: Some(target_pos),
+                                    },
+                                )
+                                .expect("Unable to insert intent");
+                            if let Some(ability) = abilities.get_mut(*ability_entity) {
+                                ability.cooldown_remaining = ability.cooldown;
+                            }
+                        } else if too_close {
+                            // Back off, and only fight in melee if we're cornered
+                            destination = flee_step(&map, my_idx, &[target_pos]);
+                            if destination.is_none() && distance < 1.5 {
+                                wants_to_melee
+                                    .insert(
+                                        entity,
+                                        WantsToMelee {
+                                            target: target_entity,
+                                        },
+                                    )
+                                    .expect("Unable to insert attack");
+                            }
+                        } else if distance < 1.5 {
+                            wants_to_melee
+                                .insert(
+                                    entity,
+                                    WantsToMelee {
+                                        target: target_entity,
+                                    },
+                                )
+                                .expect("Unable to insert attack");
+                        } else if keeps_distance.get(entity).is_none()
+                            || distance > *ability_reach.get(&entity).unwrap_or(&0) as f32
+                        {
+                            // Pack members spread out to surround the target rather than queueing
+                            if pack.is_some() {
+                                destination = goal_maps.step_to_surround(&map, my_idx, target_idx);
+                            }
+
+                            // Ranged attackers hold their ground while they wait to use an ability
+                            if destination.is_none() {
+                                destination = if target_entity == *player_entity {
+                                    NavigationMaps::downhill(
+                                        &map,
+                                        my_idx,
+                                        &navigation.towards_player,
+                                    )
+                                } else {
+                                    goal_maps.step_towards(&map, my_idx, target_idx)
+                                };
+                            }
+                        }
+                    }
+                }
+                AiState::Searching { x, y, turns } => {
+                    let last_seen_idx = map.xy_idx(x, y);
+                    destination = goal_maps.step_towards(&map, my_idx, last_seen_idx);
+                    if destination.is_none() {
+                        // We're where we last saw them, so poke around the area for a while
+                        let exits = map.get_available_exits(my_idx);
+                        if !exits.is_empty() {
+                            let roll = rng.roll_dice(1, exits.len() as i32) - 1;
+                            destination = Some(exits[roll as usize].0);
+                        }
+                        behavior.state = if turns > 1 {
+                            AiState::Searching {
+                                x,
+                                y,
+                                turns: turns - 1,
+                            }
+                        } else {
+                            AiState::Idle
+                        };
                     }
                 }
+                AiState::Fleeing => {
+                    destination = if threats.contains(&*player_pos) {
+                        NavigationMaps::downhill(&map, my_idx, &navigation.away_from_player)
+                    } else {
+                        flee_step(&map, my_idx, &threats)
+                    };
+                }
+            }
+            if uses_doors {
+                for door_idx in closed_doors.keys() {
+                    map.blocked[*door_idx] = true;
+                }
+            }
+
+            // If there's a closed door in the way, deal with it instead of moving
+            if let Some(door) = destination.and_then(|idx| closed_doors.get(&idx)) {
+                if opens_doors.get(entity).is_some() {
+                    wants_open
+                        .insert(entity, WantsToOpenDoor { door: *door })
+                        .expect("Unable to insert intent");
+                } else {
+                    wants_bash
+                        .insert(entity, WantsToBashDoor { door: *door })
+                        .expect("Unable to insert intent");
+                }
+                destination = None;
+            }
+
+            if let Some(destination) = destination.filter(|idx| *idx != player_idx) {
+                map.blocked[my_idx] = false;
+                pos.x = destination as i32 % map.width;
+                pos.y = destination as i32 / map.width;
+                entity_moved
+                    .insert(entity, EntityMoved {})
+                    .expect("Unable to insert marker");
+                map.blocked[destination] = true;
+                viewshed.dirty = true;
+            }
+        }
+        map.blocked[player_idx] = false;
+
+        // Alerted packs join the hunt, even those who were asleep
+        for (entity, behavior) in (&entities, &mut behaviors).join() {
+            if let Some(alert) = pack_of(entity).and_then(|pack| pack_alerts.get(&pack)) {
+                if !matches!(behavior.state, AiState::Chasing { .. } | AiState::Fleeing) {
+                    behavior.state = AiState::Chasing {
+                        x: alert.x,
+                        y: alert.y,
+                    };
+                }
             }
         }
     }
 }
+
+/// Picks the exit that leaves us furthest from everything scary
+fn flee_step(map: &Map, start: usize, threats: &[Point]) -> Option<usize> {
+    let safety = |idx: usize| -> f32 {
+        let pt = Point::new(idx as i32 % map.width, idx as i32 / map.width);
+        threats
+            .iter()
+            .map(|t| rltk::DistanceAlg::Pythagoras.distance2d(pt, *t))
+            .sum::<f32>()
+    };
+
+    let mut best_score = safety(start);
+    let mut destination = None;
+    for (exit, _cost) in map.get_available_exits(start).iter() {
+        let score = safety(*exit);
+        if score > best_score {
+            best_score = score;
+            destination = Some(*exit);
+        }
+    }
+    destination
+}
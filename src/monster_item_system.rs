@@ -0,0 +1,209 @@
+use super::{
+    AiState, AreaOfEffect, Behavior, Charges, CombatStats, Confusion, Cursed, DefenseBonus,
+    Durability, Equippable, Equipped, InBackpack, InflictsDamage, Item, Map, MeleePowerBonus,
+    Perishable, Position, ProvidesHealing, Ranged, RunState, UsesItems, Viewshed,
+    WantsToPickupItem, WantsToUseItem,
+};
+use rltk::Point;
+use specs::prelude::*;
+
+/// Monsters drink a healing potion once they're down to this share of their hit points
+const QUAFF_BELOW_PERCENT: i32 = 50;
+
+/// Lets item-using monsters decide whether to spend their turn on an item. Any intent
+/// raised here takes the place of the monster's normal action in `MonsterAI`.
+pub struct MonsterItemSystem {}
+
+impl<'a> System<'a> for MonsterItemSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        ReadExpect<'a, RunState>,
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, Point>,
+        Entities<'a>,
+        ReadStorage<'a, UsesItems>,
+        ReadStorage<'a, Behavior>,
+        ReadStorage<'a, CombatStats>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Viewshed>,
+        ReadStorage<'a, Confusion>,
+        ReadStorage<'a, InBackpack>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, Equippable>,
+        ReadStorage<'a, MeleePowerBonus>,
+        ReadStorage<'a, DefenseBonus>,
+        ReadStorage<'a, ProvidesHealing>,
+        ReadStorage<'a, InflictsDamage>,
+        ReadStorage<'a, Ranged>,
+        ReadStorage<'a, AreaOfEffect>,
+        ReadStorage<'a, Item>,
+        ReadStorage<'a, Cursed>,
+        ReadStorage<'a, Charges>,
+        ReadStorage<'a, Durability>,
+        ReadStorage<'a, Perishable>,
+        WriteStorage<'a, WantsToUseItem>,
+        WriteStorage<'a, WantsToPickupItem>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            runstate,
+            map,
+            player_pos,
+            entities,
+            uses_items,
+            behaviors,
+            combat_stats,
+            positions,
+            viewsheds,
+            confused,
+            backpack,
+            equipped,
+            equippable,
+            melee_bonuses,
+            defense_bonuses,
+            healing,
+            inflicts_damage,
+            ranged,
+            aoe,
+            items,
+            cursed,
+            charges,
+            durability,
+            perishable,
+            mut wants_use,
+            mut wants_pickup,
+        ) = data;
+
+        if *runstate != RunState::MonsterTurn {
+            return;
+        }
+
+        // How much good an item does in its equipment slot
+        let equipment_score = |item: Entity| -> i32 {
+            let bonus = melee_bonuses.get(item).map_or(0, |b| b.power)
+                + defense_bonuses.get(item).map_or(0, |b| b.defense);
+            durability
+                .get(item)
+                .map_or(bonus, |d| d.effective_bonus(bonus))
+        };
+
+        for (entity, _uses_items, behavior, stats, pos, viewshed) in (
+            &entities,
+            &uses_items,
+            &behaviors,
+            &combat_stats,
+            &positions,
+            &viewsheds,
+        )
+            .join()
+        {
+            if behavior.state == AiState::Sleeping || confused.get(entity).is_some() {
+                continue;
+            }
+
+            let carried: Vec<Entity> = (&entities, &backpack)
+                .join()
+                .filter(|(_item, pack)| pack.owner == entity)
+                .map(|(item, _pack)| item)
+                .collect();
+
+            // Patch ourselves up when badly hurt
+            if stats.hp * 100 < stats.max_hp * QUAFF_BELOW_PERCENT {
+                if let Some(potion) = carried.iter().find(|item| healing.get(**item).is_some()) {
+                    wants_use
+                        .insert(
+                            entity,
+                            WantsToUseItem {
+                                item: *potion,
+                                target: None,
+                            },
+                        )
+                        .expect("Unable to insert intent");
+                    continue;
+                }
+            }
+
+            // Read anything offensive at the player, as long as we won't be caught in the blast
+            let hunting_player = matches!(behavior.state, AiState::Chasing { .. })
+                && viewshed.visible_tiles.contains(&player_pos);
+            if hunting_player {
+                let distance =
+                    rltk::DistanceAlg::Pythagoras.distance2d(Point::new(pos.x, pos.y), *player_pos);
+                let scroll = carried.iter().find(|item| {
+                    let offensive =
+                        inflicts_damage.get(**item).is_some() || confused.get(**item).is_some();
+                    let in_range = ranged
+                        .get(**item)
+                        .is_some_and(|r| distance <= r.range as f32);
+                    let safe = aoe
+                        .get(**item)
+                        .is_none_or(|area| distance > area.radius as f32);
+                    let charged = charges.get(**item).is_none_or(|c| c.current > 0);
+                    offensive && in_range && safe && charged
+                });
+                if let Some(scroll) = scroll {
+                    wants_use
+                        .insert(
+                            entity,
+                            WantsToUseItem {
+                                item: *scroll,
+                                target: Some(*player_pos),
+                            },
+                        )
+                        .expect("Unable to insert intent");
+                    continue;
+                }
+            }
+
+            // Swap in better gear when we have it, unless we're stuck with something cursed
+            let upgrade = carried.iter().find(|item| {
+                equippable.get(**item).is_some_and(|can_equip| {
+                    let current = (&entities, &equipped)
+                        .join()
+                        .find(|(_e, eq)| eq.owner == entity && eq.slot == can_equip.slot);
+                    match current {
+                        Some((e, _eq)) if cursed.get(e).is_some() => false,
+                        Some((e, _eq)) => equipment_score(**item) > equipment_score(e),
+                        None => equipment_score(**item) > 0,
+                    }
+                })
+            });
+            if let Some(upgrade) = upgrade {
+                wants_use
+                    .insert(
+                        entity,
+                        WantsToUseItem {
+                            item: *upgrade,
+                            target: None,
+                        },
+                    )
+                    .expect("Unable to insert intent");
+                continue;
+            }
+
+            // Grab anything we happen to be standing on, unless we have better things to do.
+            // Food that goes off (corpses included) is left where it lies.
+            if matches!(behavior.state, AiState::Chasing { .. } | AiState::Fleeing) {
+                continue;
+            }
+            let idx = map.xy_idx(pos.x, pos.y);
+            let loot = map.tile_content[idx].iter().find(|e| {
+                items.get(**e).is_some()
+                    && positions.get(**e).is_some()
+                    && perishable.get(**e).is_none()
+            });
+            if let Some(loot) = loot {
+                wants_pickup
+                    .insert(
+                        entity,
+                        WantsToPickupItem {
+                            collected_by: entity,
+                            item: *loot,
+                        },
+                    )
+                    .expect("Unable to insert intent");
+            }
+        }
+    }
+}
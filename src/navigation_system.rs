@@ -0,0 +1,224 @@
+use super::{Door, Map, Position, RunState, TileType};
+use rltk::{BaseMap, Point, RandomNumberGenerator};
+use specs::prelude::*;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+/// Scale applied to the approach map to seed the flee map; anything below -1 makes
+/// fleeing monsters prefer long escape routes over dead ends near the player
+const FLEE_WEIGHT: f32 = -1.2;
+
+/// Wandering monsters head for landmarks spaced this far apart, rather than anywhere at all,
+/// so that they end up sharing flow maps
+const LANDMARK_SPACING: i32 = 16;
+
+/// Dijkstra flow maps shared by every monster, rebuilt once per monster turn
+#[derive(Default)]
+pub struct NavigationMaps {
+    pub towards_player: Vec<f32>,
+    pub away_from_player: Vec<f32>,
+}
+
+impl NavigationMaps {
+    /// Picks the open neighbouring tile that is furthest downhill on a flow map
+    pub fn downhill(map: &Map, idx: usize, flow: &[f32]) -> Option<usize> {
+        if flow.len() != map.tiles.len() {
+            return None;
+        }
+
+        let mut best = flow[idx];
+        let mut destination = None;
+        for (exit, _cost) in map.get_available_exits(idx).iter() {
+            if flow[*exit] < best {
+                best = flow[*exit];
+                destination = Some(*exit);
+            }
+        }
+        destination
+    }
+}
+
+/// Flow maps towards wherever monsters are heading this turn, other than the player. Each is
+/// built the first time a monster needs it and shared by everyone going the same way, so a
+/// crowd costs no more than a single monster.
+pub struct GoalMaps {
+    doors: HashSet<usize>,
+    towards: HashMap<usize, Vec<f32>>,
+    surround: HashMap<usize, Vec<f32>>,
+    landmarks: Vec<usize>,
+}
+
+impl GoalMaps {
+    pub fn new(doors: HashSet<usize>) -> Self {
+        Self {
+            doors,
+            towards: HashMap::new(),
+            surround: HashMap::new(),
+            landmarks: Vec::new(),
+        }
+    }
+
+    /// The next step from `start` towards `goal`, even if someone is standing on the goal
+    pub fn step_towards(&mut self, map: &Map, start: usize, goal: usize) -> Option<usize> {
+        if start == goal {
+            return None;
+        }
+
+        let doors = &self.doors;
+        let flow = self.towards.entry(goal).or_insert_with(|| {
+            let mut passable = doors.clone();
+            passable.insert(goal);
+            flow_map(map, &passable, &[(goal, 0.0)])
+        });
+        NavigationMaps::downhill(map, start, flow)
+    }
+
+    /// The next step from `start` towards the nearest open tile around `target`, so a pack
+    /// spreads out to surround its prey rather than queueing behind each other
+    pub fn step_to_surround(&mut self, map: &Map, start: usize, target: usize) -> Option<usize> {
+        let doors = &self.doors;
+        let flow = self.surround.entry(target).or_insert_with(|| {
+            let x = target as i32 % map.width;
+            let y = target as i32 / map.width;
+            let mut seeds: Vec<(usize, f32)> = Vec::new();
+            for (dx, dy) in NEIGHBOURS {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 1 || nx > map.width - 1 || ny < 1 || ny > map.height - 1 {
+                    continue;
+                }
+                let idx = map.xy_idx(nx, ny);
+                if map.tiles[idx] != TileType::Wall && !map.blocked[idx] {
+                    seeds.push((idx, 0.0));
+                }
+            }
+            flow_map(map, doors, &seeds)
+        });
+        NavigationMaps::downhill(map, start, flow)
+    }
+
+    /// Picks a landmark for a wandering monster to head for
+    pub fn random_landmark(&mut self, map: &Map, rng: &mut RandomNumberGenerator) -> Option<usize> {
+        if self.landmarks.is_empty() {
+            self.landmarks = landmarks(map);
+        }
+        if self.landmarks.is_empty() {
+            return None;
+        }
+        let roll = rng.roll_dice(1, self.landmarks.len() as i32) - 1;
+        Some(self.landmarks[roll as usize])
+    }
+}
+
+/// The floor tile nearest the middle of each `LANDMARK_SPACING` square of the map
+fn landmarks(map: &Map) -> Vec<usize> {
+    let mut landmarks = Vec::new();
+    for top in (0..map.height).step_by(LANDMARK_SPACING as usize) {
+        for left in (0..map.width).step_by(LANDMARK_SPACING as usize) {
+            let center = (left + LANDMARK_SPACING / 2, top + LANDMARK_SPACING / 2);
+            let nearest = (top..i32::min(top + LANDMARK_SPACING, map.height))
+                .flat_map(|y| {
+                    (left..i32::min(left + LANDMARK_SPACING, map.width)).map(move |x| (x, y))
+                })
+                .filter(|(x, y)| map.tiles[map.xy_idx(*x, *y)] == TileType::Floor)
+                .min_by_key(|(x, y)| (x - center.0).pow(2) + (y - center.1).pow(2));
+            if let Some((x, y)) = nearest {
+                landmarks.push(map.xy_idx(x, y));
+            }
+        }
+    }
+    landmarks
+}
+
+pub struct NavigationSystem {}
+
+impl<'a> System<'a> for NavigationSystem {
+    type SystemData = (
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, Point>,
+        ReadExpect<'a, RunState>,
+        WriteExpect<'a, NavigationMaps>,
+        ReadStorage<'a, Door>,
+        ReadStorage<'a, Position>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (map, player_pos, runstate, mut navigation, doors, positions) = data;
+
+        if *runstate != RunState::MonsterTurn {
+            return;
+        }
+
+        // Routes lead through doors; monsters that can't use them will stop there
+        let door_tiles: HashSet<usize> = (&doors, &positions)
+            .join()
+            .map(|(_door, pos)| map.xy_idx(pos.x, pos.y))
+            .collect();
+
+        let player_idx = map.xy_idx(player_pos.x, player_pos.y);
+        navigation.towards_player = flow_map(&map, &door_tiles, &[(player_idx, 0.0)]);
+
+        let flee_seeds: Vec<(usize, f32)> = navigation
+            .towards_player
+            .iter()
+            .enumerate()
+            .filter(|(_idx, distance)| **distance < f32::MAX)
+            .map(|(idx, distance)| (idx, *distance * FLEE_WEIGHT))
+            .collect();
+        navigation.away_from_player = flow_map(&map, &door_tiles, &flee_seeds);
+    }
+}
+
+/// Offsets to the eight tiles around any tile
+pub const NEIGHBOURS: [(i32, i32); 8] = [
+    (-1, 0),
+    (1, 0),
+    (0, -1),
+    (0, 1),
+    (-1, -1),
+    (1, -1),
+    (-1, 1),
+    (1, 1),
+];
+
+/// Builds a Dijkstra map from the given starting values. Blocked tiles (usually monsters)
+/// receive a value so whoever stands there can read it, but nothing flows through them.
+fn flow_map(map: &Map, doors: &HashSet<usize>, seeds: &[(usize, f32)]) -> Vec<f32> {
+    let mut flow = vec![f32::MAX; map.tiles.len()];
+    let mut open_list: BinaryHeap<(Reverse<i32>, usize)> = BinaryHeap::new();
+    for (idx, value) in seeds.iter() {
+        flow[*idx] = *value;
+        if !map.blocked[*idx] || doors.contains(idx) {
+            open_list.push((Reverse((*value * 100.0) as i32), *idx));
+        }
+    }
+
+    while let Some((Reverse(key), idx)) = open_list.pop() {
+        if key > (flow[idx] * 100.0) as i32 {
+            continue; // We've already found a better route here
+        }
+
+        let x = idx as i32 % map.width;
+        let y = idx as i32 / map.width;
+        for (dx, dy) in NEIGHBOURS {
+            let (nx, ny) = (x + dx, y + dy);
+            if nx < 1 || nx > map.width - 1 || ny < 1 || ny > map.height - 1 {
+                continue;
+            }
+            let neighbor = map.xy_idx(nx, ny);
+            if map.tiles[neighbor] == TileType::Wall {
+                continue;
+            }
+
+            let step = if dx == 0 || dy == 0 { 1.0 } else { 1.45 };
+            let value = flow[idx] + step;
+            if value < flow[neighbor] {
+                flow[neighbor] = value;
+                if !map.blocked[neighbor] || doors.contains(&neighbor) {
+                    open_list.push((Reverse((value * 100.0) as i32), neighbor));
+                }
+            }
+        }
+    }
+
+    flow
+}
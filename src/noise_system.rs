@@ -0,0 +1,76 @@
+use super::{monster_ai_system::SEARCH_TURNS, AiState, Behavior, Position, RunState};
+use rltk::Point;
+use specs::prelude::*;
+
+/// How far away (in tiles) various actions can be heard
+pub const FOOTSTEP_VOLUME: i32 = 2;
+pub const DOOR_VOLUME: i32 = 6;
+pub const COMBAT_VOLUME: i32 = 8;
+pub const BASH_VOLUME: i32 = 12;
+pub const SHOUT_VOLUME: i32 = 16;
+
+struct Noise {
+    x: i32,
+    y: i32,
+    volume: i32,
+}
+
+pub struct NoiseBuilder {
+    noises: Vec<Noise>,
+}
+
+impl NoiseBuilder {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self { noises: Vec::new() }
+    }
+
+    pub fn request(&mut self, x: i32, y: i32, volume: i32) {
+        self.noises.push(Noise { x, y, volume })
+    }
+}
+
+pub struct HearingSystem {}
+
+impl<'a> System<'a> for HearingSystem {
+    type SystemData = (
+        ReadExpect<'a, RunState>,
+        WriteExpect<'a, NoiseBuilder>,
+        ReadStorage<'a, Position>,
+        WriteStorage<'a, Behavior>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (runstate, mut noise_builder, positions, mut behaviors) = data;
+
+        // Noise made since the monsters last acted is all heard at once, on their turn
+        if *runstate != RunState::MonsterTurn {
+            return;
+        }
+
+        for noise in noise_builder.noises.iter() {
+            let origin = Point::new(noise.x, noise.y);
+            for (pos, behavior) in (&positions, &mut behaviors).join() {
+                let distance =
+                    rltk::DistanceAlg::Pythagoras.distance2d(origin, Point::new(pos.x, pos.y));
+                let heard = match behavior.state {
+                    // Sleepers only stir for noises half as far away
+                    AiState::Sleeping => distance * 2.0 <= noise.volume as f32,
+                    AiState::Idle | AiState::Wandering { .. } | AiState::Searching { .. } => {
+                        distance <= noise.volume as f32
+                    }
+                    AiState::Chasing { .. } | AiState::Fleeing => false,
+                };
+                if heard {
+                    behavior.state = AiState::Searching {
+                        x: noise.x,
+                        y: noise.y,
+                        turns: SEARCH_TURNS,
+                    };
+                }
+            }
+        }
+
+        noise_builder.noises.clear();
+    }
+}
@@ -1,13 +1,58 @@
 use super::{
-    gamelog::GameLog, BlocksTile, BlocksVisibility, CombatStats, Door, EntityMoved, HungerClock,
-    HungerState, Item, Map, Monster, Player, Position, Renderable, RunState, State, TileType,
-    Viewshed, WantsToMelee, WantsToPickupItem,
+    gamelog::GameLog,
+    gui::VendorMode,
+    inventory_system::{stack_weight, stow_item, use_up_one},
+    noise_system::{NoiseBuilder, FOOTSTEP_VOLUME, SHOUT_VOLUME},
+    spawner, Attributes, AutoPickup, BlocksTile, Boss, CombatStats, Confusion, Container,
+    CurseKnown, Cursed, Door, Durability, Encumbrance, EntityMoved, Equippable, Gold, HungerClock,
+    HungerState, InBackpack, InflictsDamage, Item, ItemIdentification, KnownSpell, Mana, Map,
+    Monster, Name, PickupCategory, Player, Position, Purse, Ranged, RunState, Sickness, Spellbook,
+    Stackable, State, SufferDamage, Throwable, TileType, Value, Vendor, Viewshed, WantsToCloseDoor,
+    WantsToMelee, WantsToOpenDoor, WantsToPickupItem, WantsToUseItem, Weight,
 };
 use rltk::{Point, Rltk, VirtualKeyCode};
 use specs::prelude::*;
 use std::cmp::{max, min};
 
-pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
+/// Counts down the player's confusion, returning true if they were confused this turn
+fn tick_player_confusion(ecs: &mut World) -> bool {
+    let player_entity = ecs.fetch::<Entity>();
+    let mut confused = ecs.write_storage::<Confusion>();
+    let mut is_confused = false;
+    if let Some(confusion) = confused.get_mut(*player_entity) {
+        is_confused = true;
+        confusion.turns -= 1;
+        if confusion.turns < 1 {
+            confused.remove(*player_entity);
+            let mut gamelog = ecs.fetch_mut::<GameLog>();
+            gamelog
+                .entries
+                .push("You are no longer confused.".to_string());
+        }
+    }
+    is_confused
+}
+
+pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState {
+    // Confused players stumble in a random direction
+    let (delta_x, delta_y) = if tick_player_confusion(ecs) {
+        let mut rng = ecs.write_resource::<rltk::RandomNumberGenerator>();
+        let mut gamelog = ecs.fetch_mut::<GameLog>();
+        gamelog
+            .entries
+            .push("You stumble around in confusion.".to_string());
+        (rng.roll_dice(1, 3) - 2, rng.roll_dice(1, 3) - 2)
+    } else {
+        (delta_x, delta_y)
+    };
+
+    // Bumping into a chest or barrel opens it
+    let bumped = container_at(ecs, delta_x, delta_y)
+        .filter(|container| ecs.read_storage::<BlocksTile>().get(*container).is_some());
+    if let Some(container) = bumped {
+        return open_container(ecs, container);
+    }
+
     let mut positions = ecs.write_storage::<Position>();
     let mut players = ecs.write_storage::<Player>();
     let mut viewsheds = ecs.write_storage::<Viewshed>();
@@ -16,11 +61,11 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
     let entities = ecs.entities();
     let mut wants_to_melee = ecs.write_storage::<WantsToMelee>();
     let mut entity_moved = ecs.write_storage::<EntityMoved>();
-    let mut doors = ecs.write_storage::<Door>();
-    let mut blocks_visibility = ecs.write_storage::<BlocksVisibility>();
-    let mut blocks_movement = ecs.write_storage::<BlocksTile>();
-    let mut renderables = ecs.write_storage::<Renderable>();
+    let doors = ecs.read_storage::<Door>();
+    let mut wants_open = ecs.write_storage::<WantsToOpenDoor>();
+    let vendors = ecs.read_storage::<Vendor>();
 
+    let mut moved = false;
     for (entity, _player, pos, viewshed) in
         (&entities, &mut players, &mut positions, &mut viewsheds).join()
     {
@@ -29,11 +74,17 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
             || pos.y + delta_y < 1
             || pos.y + delta_y > map.height - 1
         {
-            return;
+            return RunState::PlayerTurn;
         }
         let destination_idx = map.xy_idx(pos.x + delta_x, pos.y + delta_y);
 
         for potential_target in map.tile_content[destination_idx].iter() {
+            if vendors.get(*potential_target).is_some() {
+                return RunState::ShowVendor {
+                    vendor: *potential_target,
+                    mode: VendorMode::Buy,
+                };
+            }
             let target = combat_stats.get(*potential_target);
             if let Some(_target) = target {
                 wants_to_melee
@@ -44,16 +95,20 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
                         },
                     )
                     .expect("Add target failed");
-                return;
+                return RunState::PlayerTurn;
             }
-            let door = doors.get_mut(*potential_target);
+            let door = doors.get(*potential_target);
             if let Some(door) = door {
-                door.open = true;
-                blocks_visibility.remove(*potential_target);
-                blocks_movement.remove(*potential_target);
-                let glyph = renderables.get_mut(*potential_target).unwrap();
-                glyph.glyph = rltk::to_cp437('/');
-                viewshed.dirty = true;
+                if !door.open {
+                    wants_open
+                        .insert(
+                            entity,
+                            WantsToOpenDoor {
+                                door: *potential_target,
+                            },
+                        )
+                        .expect("Unable to insert intent");
+                }
             }
         }
         if !map.blocked[destination_idx] {
@@ -64,11 +119,393 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
                 .expect("Unable to insert marker");
 
             viewshed.dirty = true;
+            let mut noise_builder = ecs.write_resource::<NoiseBuilder>();
+            noise_builder.request(pos.x, pos.y, FOOTSTEP_VOLUME);
             let mut ppos = ecs.write_resource::<Point>();
             ppos.x = pos.x;
             ppos.y = pos.y;
+            moved = true;
+        }
+    }
+
+    // Walking onto a tile sweeps up anything the player has chosen to collect
+    if moved {
+        std::mem::drop(positions);
+        auto_pickup(ecs);
+    }
+    RunState::PlayerTurn
+}
+
+/// Buys an item from a vendor's stock, if the player can afford it
+pub fn buy_item(ecs: &mut World, vendor: Entity, item: Entity) {
+    let player_entity = *ecs.fetch::<Entity>();
+    let price = {
+        let depth = ecs.fetch::<Map>().depth;
+        let vendors = ecs.read_storage::<Vendor>();
+        let values = ecs.read_storage::<Value>();
+        let purses = ecs.read_storage::<Purse>();
+        let encumbrance = ecs.read_storage::<Encumbrance>();
+        let mut gamelog = ecs.fetch_mut::<GameLog>();
+
+        let (vendor_info, value, purse) = match (
+            vendors.get(vendor),
+            values.get(item),
+            purses.get(player_entity),
+        ) {
+            (Some(vendor_info), Some(value), Some(purse)) => (vendor_info, value, purse),
+            _ => return,
+        };
+
+        let price = vendor_info.buy_price(value, depth);
+        if purse.gold < price {
+            gamelog.entries.push("You can't afford that.".to_string());
+            return;
+        }
+
+        let pounds = ecs
+            .read_storage::<Weight>()
+            .get(item)
+            .map_or(0.0, |w| w.pounds);
+        if let Some(load) = encumbrance.get(player_entity) {
+            if !load.can_lift(pounds) {
+                gamelog
+                    .entries
+                    .push("You couldn't carry that as well.".to_string());
+                return;
+            }
+        }
+        price
+    };
+
+    // Only one item comes off a stack at a time
+    let item = spawner::split_stack(ecs, item);
+    let pounds = ecs
+        .read_storage::<Weight>()
+        .get(item)
+        .map_or(0.0, |w| w.pounds);
+    if let Some(load) = ecs.write_storage::<Encumbrance>().get_mut(player_entity) {
+        load.carried += pounds;
+    }
+    if let Some(purse) = ecs.write_storage::<Purse>().get_mut(player_entity) {
+        purse.gold -= price;
+    }
+    let name = ecs
+        .fetch::<ItemIdentification>()
+        .display_name(&ecs.read_storage::<Name>().get(item).unwrap().name);
+    stow_item(ecs, player_entity, item);
+    ecs.fetch_mut::<GameLog>()
+        .entries
+        .push(format!("You buy the {} for {} gold.", name, price));
+}
+
+/// Sells an item from the player's backpack to a vendor, who adds it to their stock
+pub fn sell_item(ecs: &mut World, vendor: Entity, item: Entity) {
+    let player_entity = *ecs.fetch::<Entity>();
+    let price = {
+        let depth = ecs.fetch::<Map>().depth;
+        let vendors = ecs.read_storage::<Vendor>();
+        let values = ecs.read_storage::<Value>();
+        match (vendors.get(vendor), values.get(item)) {
+            (Some(vendor_info), Some(value)) => vendor_info.sell_price(value, depth),
+            _ => return,
+        }
+    };
+
+    // Only one item comes off a stack at a time
+    let item = spawner::split_stack(ecs, item);
+    if let Some(purse) = ecs.write_storage::<Purse>().get_mut(player_entity) {
+        purse.gold += price;
+    }
+    if let (Some(load), Some(weight)) = (
+        ecs.write_storage::<Encumbrance>().get_mut(player_entity),
+        ecs.read_storage::<Weight>().get(item),
+    ) {
+        load.carried -= weight.pounds;
+    }
+    let name = ecs
+        .fetch::<ItemIdentification>()
+        .display_name(&ecs.read_storage::<Name>().get(item).unwrap().name);
+    stow_item(ecs, vendor, item);
+    ecs.fetch_mut::<GameLog>()
+        .entries
+        .push(format!("You sell the {} for {} gold.", name, price));
+}
+
+/// Finds a container on the tile at the given offset from the player
+fn container_at(ecs: &World, delta_x: i32, delta_y: i32) -> Option<Entity> {
+    let player_pos = ecs.fetch::<Point>();
+    let map = ecs.fetch::<Map>();
+    let containers = ecs.read_storage::<Container>();
+
+    let (x, y) = (player_pos.x + delta_x, player_pos.y + delta_y);
+    if x < 1 || x > map.width - 1 || y < 1 || y > map.height - 1 {
+        return None;
+    }
+    let idx = map.xy_idx(x, y);
+    map.tile_content[idx]
+        .iter()
+        .find(|entity| containers.get(**entity).is_some())
+        .copied()
+}
+
+/// Opens a container, setting off any trap on it, and shows what's inside
+fn open_container(ecs: &mut World, container: Entity) -> RunState {
+    let player_entity = ecs.fetch::<Entity>();
+    let names = ecs.read_storage::<Name>();
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+    let name = names
+        .get(container)
+        .map_or("container", |n| n.name.as_str());
+
+    if let Some(trap) = ecs.write_storage::<InflictsDamage>().remove(container) {
+        SufferDamage::new_damage(
+            &mut ecs.write_storage::<SufferDamage>(),
+            *player_entity,
+            trap.damage,
+        );
+        gamelog.entries.push(format!(
+            "A needle springs from the {}'s lock, pricking you for {} hp!",
+            name, trap.damage
+        ));
+    }
+
+    let empty = !ecs
+        .read_storage::<InBackpack>()
+        .join()
+        .any(|pack| pack.owner == container);
+    if empty {
+        gamelog.entries.push(format!("The {} is empty.", name));
+        return RunState::PlayerTurn;
+    }
+    gamelog.entries.push(format!(
+        "You look inside the {}. SPACE takes everything.",
+        name
+    ));
+    RunState::ShowContainer { container }
+}
+
+/// Opens a container underfoot, or failing that one next to the player
+fn open_nearby_container(ecs: &mut World) -> RunState {
+    let nearby = [
+        (0, 0),
+        (-1, 0),
+        (1, 0),
+        (0, -1),
+        (0, 1),
+        (-1, -1),
+        (1, -1),
+        (-1, 1),
+        (1, 1),
+    ]
+    .iter()
+    .find_map(|(delta_x, delta_y)| container_at(ecs, *delta_x, *delta_y));
+
+    match nearby {
+        None => {
+            let mut gamelog = ecs.fetch_mut::<GameLog>();
+            gamelog
+                .entries
+                .push("There is nothing here to open.".to_string());
+            RunState::AwaitingInput
+        }
+        Some(container) => open_container(ecs, container),
+    }
+}
+
+/// Moves an item from a container to the player's backpack, if they can carry it
+pub fn take_from_container(ecs: &mut World, container: Entity, item: Entity) {
+    let player_entity = ecs.fetch::<Entity>();
+    let names = ecs.read_storage::<Name>();
+    let identification = ecs.fetch::<ItemIdentification>();
+    let mut purses = ecs.write_storage::<Purse>();
+    let mut encumbrance = ecs.write_storage::<Encumbrance>();
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+
+    let container_name = names
+        .get(container)
+        .map_or("container", |n| n.name.as_str());
+
+    // Gold goes straight into the purse
+    if let (Some(gold), Some(purse)) = (
+        ecs.read_storage::<Gold>().get(item),
+        purses.get_mut(*player_entity),
+    ) {
+        purse.gold += gold.amount;
+        gamelog.entries.push(format!(
+            "You take {} gold from the {}.",
+            gold.amount, container_name
+        ));
+        ecs.entities().delete(item).expect("Delete failed");
+        return;
+    }
+
+    let item_name = identification.display_name(&names.get(item).unwrap().name);
+    let pounds = stack_weight(
+        ecs.read_storage::<Weight>().get(item),
+        ecs.read_storage::<Stackable>().get(item),
+    );
+    if let Some(load) = encumbrance.get_mut(*player_entity) {
+        if !load.can_lift(pounds) {
+            gamelog.entries.push(format!(
+                "You can't carry the {} as well; it's too heavy.",
+                item_name
+            ));
+            return;
+        }
+        load.carried += pounds;
+    }
+
+    stow_item(ecs, *player_entity, item);
+    gamelog.entries.push(format!(
+        "You take the {} from the {}.",
+        item_name, container_name
+    ));
+}
+
+/// Empties a container into the player's backpack, leaving behind anything too heavy
+pub fn take_all_from_container(ecs: &mut World, container: Entity) {
+    let contents: Vec<Entity> = {
+        let entities = ecs.entities();
+        let backpack = ecs.read_storage::<InBackpack>();
+        (&entities, &backpack)
+            .join()
+            .filter(|(_item, pack)| pack.owner == container)
+            .map(|(item, _pack)| item)
+            .collect()
+    };
+    for item in contents {
+        take_from_container(ecs, container, item);
+    }
+}
+
+/// Studies a spellbook, committing its spell to memory for good
+pub fn learn_spell(ecs: &mut World, book: Entity) -> RunState {
+    let player_entity = *ecs.fetch::<Entity>();
+    let spell = match ecs.read_storage::<Spellbook>().get(book) {
+        None => return RunState::AwaitingInput,
+        Some(book) => book.spell.to_string(),
+    };
+
+    let known = (
+        &ecs.read_storage::<KnownSpell>(),
+        &ecs.read_storage::<Name>(),
+    )
+        .join()
+        .any(|(known, name)| known.owner == player_entity && name.name == spell);
+    if known {
+        let mut gamelog = ecs.fetch_mut::<GameLog>();
+        gamelog.entries.push(format!("You already know {}.", spell));
+        return RunState::AwaitingInput;
+    }
+
+    spawner::spell(ecs, player_entity, &spell);
+    ecs.delete_entity(book).expect("Unable to delete");
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+    gamelog.entries.push(format!(
+        "You study the book until you know {} by heart. It crumbles to dust.",
+        spell
+    ));
+    RunState::PlayerTurn
+}
+
+/// Casts a known spell, if the player has the mana for it, picking a target first if it
+/// needs one
+pub fn cast_spell(ecs: &mut World, spell: Entity) -> RunState {
+    let player_entity = ecs.fetch::<Entity>();
+    let names = ecs.read_storage::<Name>();
+    let known_spells = ecs.read_storage::<KnownSpell>();
+    let attributes = ecs.read_storage::<Attributes>();
+    let mana = ecs.read_storage::<Mana>();
+
+    let base_cost = match known_spells.get(spell) {
+        None => return RunState::AwaitingInput,
+        Some(known) => known.mana_cost,
+    };
+    let cost = attributes
+        .get(*player_entity)
+        .map_or(base_cost, |a| a.mana_cost(base_cost));
+    if mana
+        .get(*player_entity)
+        .is_none_or(|pool| pool.current < cost)
+    {
+        let mut gamelog = ecs.fetch_mut::<GameLog>();
+        gamelog.entries.push(format!(
+            "You don't have enough mana to cast {}.",
+            names.get(spell).unwrap().name
+        ));
+        return RunState::AwaitingInput;
+    }
+
+    if let Some(ranged) = ecs.read_storage::<Ranged>().get(spell) {
+        return RunState::ShowTargeting {
+            range: ranged.range,
+            item: spell,
+        };
+    }
+    ecs.write_storage::<WantsToUseItem>()
+        .insert(
+            *player_entity,
+            WantsToUseItem {
+                item: spell,
+                target: None,
+            },
+        )
+        .expect("Unable to insert intent");
+    RunState::PlayerTurn
+}
+
+/// Reads a scroll of identify on one of the player's unidentified items
+pub fn identify_item(ecs: &mut World, scroll: Entity, item: Entity) {
+    let name = ecs
+        .read_storage::<Name>()
+        .get(item)
+        .unwrap()
+        .name
+        .to_string();
+    let mut identification = ecs.fetch_mut::<ItemIdentification>();
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+
+    let old_name = identification.display_name(&name);
+    if identification.identify(&name) {
+        gamelog
+            .entries
+            .push(format!("The {} turns out to be a {}.", old_name, name));
+    }
+    identification.identify("Scroll of Identify");
+
+    // Equipment reveals whether or not it's cursed
+    if ecs.read_storage::<Equippable>().get(item).is_some() {
+        let cursed = ecs.read_storage::<Cursed>().get(item).is_some();
+        if cursed {
+            gamelog.entries.push(format!("The {} is cursed!", name));
+        } else {
+            gamelog
+                .entries
+                .push(format!("The {} is free of curses.", name));
         }
+        ecs.write_storage::<CurseKnown>()
+            .insert(item, CurseKnown {})
+            .expect("Unable to insert curse knowledge");
     }
+
+    use_up_one(ecs, scroll);
+}
+
+/// Uses a repair kit to restore an item to its full durability
+pub fn repair_item(ecs: &mut World, kit: Entity, item: Entity) {
+    let mut durability = ecs.write_storage::<Durability>();
+    let names = ecs.read_storage::<Name>();
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+
+    if let Some(durability) = durability.get_mut(item) {
+        durability.current = durability.max;
+        gamelog.entries.push(format!(
+            "You patch up the {} as good as new.",
+            names.get(item).unwrap().name
+        ));
+    }
+
+    use_up_one(ecs, kit);
 }
 
 pub fn try_next_level(ecs: &mut World) -> bool {
@@ -76,6 +513,17 @@ pub fn try_next_level(ecs: &mut World) -> bool {
     let map = ecs.fetch::<Map>();
     let player_idx = map.xy_idx(player_pos.x, player_pos.y);
     if map.tiles[player_idx] == TileType::DownStairs {
+        // A living boss keeps the way down sealed
+        let bosses = ecs.read_storage::<Boss>();
+        let names = ecs.read_storage::<Name>();
+        if let Some((_boss, name)) = (&bosses, &names).join().next() {
+            let mut gamelog = ecs.fetch_mut::<GameLog>();
+            gamelog.entries.push(format!(
+                "The stairs are sealed. {} must be defeated first.",
+                name.name
+            ));
+            return false;
+        }
         true
     } else {
         let mut gamelog = ecs.fetch_mut::<GameLog>();
@@ -86,41 +534,128 @@ pub fn try_next_level(ecs: &mut World) -> bool {
     }
 }
 
-fn get_item(ecs: &mut World) {
+/// The items lying on the player's tile
+pub fn items_here(ecs: &World) -> Vec<Entity> {
     let player_pos = ecs.fetch::<Point>();
-    let player_entity = ecs.fetch::<Entity>();
     let entities = ecs.entities();
     let items = ecs.read_storage::<Item>();
     let positions = ecs.read_storage::<Position>();
-    let mut gamelog = ecs.fetch_mut::<GameLog>();
+    (&entities, &items, &positions)
+        .join()
+        .filter(|(_item, _i, pos)| pos.x == player_pos.x && pos.y == player_pos.y)
+        .map(|(item, _i, _pos)| item)
+        .collect()
+}
+
+/// Has the player pick up the given items this turn. The intents are keyed by item rather
+/// than by the player, so that more than one can be collected at once.
+pub fn pick_up(ecs: &World, items: &[Entity]) {
+    let player_entity = ecs.fetch::<Entity>();
+    let mut pickup = ecs.write_storage::<WantsToPickupItem>();
+    for item in items.iter() {
+        pickup
+            .insert(
+                *item,
+                WantsToPickupItem {
+                    collected_by: *player_entity,
+                    item: *item,
+                },
+            )
+            .expect("Unable to insert want to pickup");
+    }
+}
 
-    let mut target_item: Option<Entity> = None;
-    for (item_entity, _item, position) in (&entities, &items, &positions).join() {
-        if position.x == player_pos.x && position.y == player_pos.y {
-            target_item = Some(item_entity);
+/// Picks up anything underfoot that the player has asked to collect automatically
+fn auto_pickup(ecs: &World) {
+    let wanted: Vec<Entity> = {
+        let settings = ecs.fetch::<AutoPickup>();
+        let gold = ecs.read_storage::<Gold>();
+        let throwable = ecs.read_storage::<Throwable>();
+        items_here(ecs)
+            .into_iter()
+            .filter(|item| {
+                let category = if gold.get(*item).is_some() {
+                    Some(PickupCategory::Gold)
+                } else if throwable.get(*item).is_some() {
+                    Some(PickupCategory::Ammunition)
+                } else {
+                    None
+                };
+                category.is_some_and(|category| settings.enabled.contains(&category))
+            })
+            .collect()
+    };
+    pick_up(ecs, &wanted);
+}
+
+/// Picks up the only item here, or offers a menu when there's more than one
+fn get_item(ecs: &mut World) -> RunState {
+    let items = items_here(ecs);
+    match items.len() {
+        0 => {
+            let mut gamelog = ecs.fetch_mut::<GameLog>();
+            gamelog
+                .entries
+                .push("There is nothing here to pick up.".to_string());
+            RunState::PlayerTurn
+        }
+        1 => {
+            pick_up(ecs, &items);
+            RunState::PlayerTurn
+        }
+        _ => {
+            let mut gamelog = ecs.fetch_mut::<GameLog>();
+            gamelog
+                .entries
+                .push("There are several things here. SPACE takes them all.".to_string());
+            RunState::ShowPickup
         }
     }
+}
 
-    match target_item {
-        None => gamelog
-            .entries
-            .push("There is nothing here to pick up.".to_string()),
-        Some(item) => {
-            let mut pickup = ecs.write_storage::<WantsToPickupItem>();
-            pickup
-                .insert(
-                    *player_entity,
-                    WantsToPickupItem {
-                        collected_by: *player_entity,
-                        item,
-                    },
-                )
-                .expect("Unable to insert want to pickup");
+fn close_door(ecs: &mut World) -> RunState {
+    let player_pos = ecs.fetch::<Point>();
+    let player_entity = ecs.fetch::<Entity>();
+    let entities = ecs.entities();
+    let doors = ecs.read_storage::<Door>();
+    let positions = ecs.read_storage::<Position>();
+
+    let adjacent_door = (&entities, &doors, &positions)
+        .join()
+        .find(|(_entity, door, pos)| {
+            door.open && (pos.x - player_pos.x).abs() <= 1 && (pos.y - player_pos.y).abs() <= 1
+        })
+        .map(|(entity, _door, _pos)| entity);
+
+    match adjacent_door {
+        None => {
+            let mut gamelog = ecs.fetch_mut::<GameLog>();
+            gamelog
+                .entries
+                .push("There is no open door nearby.".to_string());
+            RunState::AwaitingInput
+        }
+        Some(door) => {
+            let mut wants_close = ecs.write_storage::<WantsToCloseDoor>();
+            wants_close
+                .insert(*player_entity, WantsToCloseDoor { door })
+                .expect("Unable to insert intent");
+            RunState::PlayerTurn
         }
     }
 }
 
+fn shout(ecs: &mut World) -> RunState {
+    let player_pos = ecs.fetch::<Point>();
+    let mut noise_builder = ecs.write_resource::<NoiseBuilder>();
+    noise_builder.request(player_pos.x, player_pos.y, SHOUT_VOLUME);
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+    gamelog.entries.push("You shout!".to_string());
+    RunState::PlayerTurn
+}
+
 fn skip_turn(ecs: &mut World) -> RunState {
+    tick_player_confusion(ecs);
     let player_entity = ecs.fetch::<Entity>();
     let viewshed_components = ecs.read_storage::<Viewshed>();
     let monsters = ecs.read_storage::<Monster>();
@@ -148,6 +683,9 @@ fn skip_turn(ecs: &mut World) -> RunState {
             _ => (),
         }
     }
+    if ecs.read_storage::<Sickness>().get(*player_entity).is_some() {
+        can_heal = false;
+    }
 
     if can_heal {
         let mut health_components = ecs.write_storage::<CombatStats>();
@@ -165,27 +703,41 @@ pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
         Some(key) => match key {
             // Cardinal Directions
             VirtualKeyCode::Left | VirtualKeyCode::Numpad4 | VirtualKeyCode::H => {
-                try_move_player(-1, 0, &mut gs.ecs)
+                return try_move_player(-1, 0, &mut gs.ecs)
             }
             VirtualKeyCode::Right | VirtualKeyCode::Numpad6 | VirtualKeyCode::L => {
-                try_move_player(1, 0, &mut gs.ecs)
+                return try_move_player(1, 0, &mut gs.ecs)
             }
             VirtualKeyCode::Up | VirtualKeyCode::Numpad8 | VirtualKeyCode::K => {
-                try_move_player(0, -1, &mut gs.ecs)
+                return try_move_player(0, -1, &mut gs.ecs)
             }
             VirtualKeyCode::Down | VirtualKeyCode::Numpad2 | VirtualKeyCode::J => {
-                try_move_player(0, 1, &mut gs.ecs)
+                return try_move_player(0, 1, &mut gs.ecs)
             }
 
             // Diagonals
-            VirtualKeyCode::Numpad9 | VirtualKeyCode::Y => try_move_player(1, -1, &mut gs.ecs),
-            VirtualKeyCode::Numpad7 | VirtualKeyCode::U => try_move_player(-1, -1, &mut gs.ecs),
-            VirtualKeyCode::Numpad3 | VirtualKeyCode::N => try_move_player(1, 1, &mut gs.ecs),
-            VirtualKeyCode::Numpad1 | VirtualKeyCode::B => try_move_player(-1, 1, &mut gs.ecs),
+            VirtualKeyCode::Numpad9 | VirtualKeyCode::Y => {
+                return try_move_player(1, -1, &mut gs.ecs)
+            }
+            VirtualKeyCode::Numpad7 | VirtualKeyCode::U => {
+                return try_move_player(-1, -1, &mut gs.ecs)
+            }
+            VirtualKeyCode::Numpad3 | VirtualKeyCode::N => {
+                return try_move_player(1, 1, &mut gs.ecs)
+            }
+            VirtualKeyCode::Numpad1 | VirtualKeyCode::B => {
+                return try_move_player(-1, 1, &mut gs.ecs)
+            }
 
             // Skip Turn
             VirtualKeyCode::Numpad5 | VirtualKeyCode::Space => return skip_turn(&mut gs.ecs),
 
+            // Doors
+            VirtualKeyCode::C => return close_door(&mut gs.ecs),
+
+            // Make some noise
+            VirtualKeyCode::S => return shout(&mut gs.ecs),
+
             // Level changes
             VirtualKeyCode::Period => {
                 if try_next_level(&mut gs.ecs) {
@@ -194,10 +746,16 @@ pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
             }
 
             // Items and Inventory
-            VirtualKeyCode::G => get_item(&mut gs.ecs),
+            VirtualKeyCode::G => return get_item(&mut gs.ecs),
+            VirtualKeyCode::A => return RunState::ShowAutoPickup,
             VirtualKeyCode::I => return RunState::ShowInventory,
             VirtualKeyCode::D => return RunState::ShowDropItem,
             VirtualKeyCode::R => return RunState::ShowRemoveItem,
+            VirtualKeyCode::T => return RunState::ShowThrowItem,
+            VirtualKeyCode::O => return open_nearby_container(&mut gs.ecs),
+
+            // Magic
+            VirtualKeyCode::Z => return RunState::ShowCastSpell,
 
             // Save and Quit
             VirtualKeyCode::Escape => return RunState::SaveGame,
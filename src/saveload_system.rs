@@ -28,9 +28,15 @@ pub fn save_game(_ecs: &mut World) {}
 pub fn save_game(ecs: &mut World) {
     // Create helper
     let mapcopy = ecs.get_mut::<super::map::Map>().unwrap().clone();
+    let uniques = (*ecs.fetch::<UniqueTracker>()).clone();
+    let identification = (*ecs.fetch::<ItemIdentification>()).clone();
     let savehelper = ecs
         .create_entity()
-        .with(SerializationHelper { map: mapcopy })
+        .with(SerializationHelper {
+            map: mapcopy,
+            uniques,
+            identification,
+        })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 
@@ -52,11 +58,22 @@ pub fn save_game(ecs: &mut World) {
             Player,
             Viewshed,
             Monster,
+            Faction,
+            Behavior,
+            KeepsDistance,
             Name,
             BlocksTile,
             BlocksVisibility,
             Door,
+            OpensDoors,
+            UsesItems,
+            Boss,
+            BashesDoors,
+            WantsToOpenDoor,
+            WantsToCloseDoor,
+            WantsToBashDoor,
             CombatStats,
+            NaturalAttacks,
             SufferDamage,
             WantsToMelee,
             Item,
@@ -66,10 +83,18 @@ pub fn save_game(ecs: &mut World) {
             AreaOfEffect,
             Confusion,
             ProvidesHealing,
+            PackMember,
+            Routed,
+            Ability,
             InBackpack,
             WantsToPickupItem,
             WantsToUseItem,
             WantsToDropItem,
+            WantsToThrowItem,
+            Teleports,
+            WantsToTeleport,
+            LightSource,
+            Fuel,
             WantsToRemoveItem,
             SerializationHelper,
             Equippable,
@@ -79,7 +104,34 @@ pub fn save_game(ecs: &mut World) {
             ParticleLifetime,
             HungerClock,
             ProvidesFood,
+            Perishable,
+            Sickness,
+            Value,
+            Gold,
+            Purse,
+            Vendor,
             MagicMapper,
+            IdentifiesItem,
+            Cursed,
+            CurseKnown,
+            RemovesCurse,
+            Charges,
+            Recharges,
+            Stackable,
+            Throwable,
+            Potion,
+            Weight,
+            Attributes,
+            Mana,
+            KnownSpell,
+            Spellbook,
+            Encumbrance,
+            Durability,
+            RepairsItem,
+            MagicItem,
+            ElementalDamage,
+            AttributeBonus,
+            Container,
             Hidden,
             EntryTrigger,
             EntityMoved,
@@ -140,11 +192,22 @@ pub fn load_game(ecs: &mut World) {
             Player,
             Viewshed,
             Monster,
+            Faction,
+            Behavior,
+            KeepsDistance,
             Name,
             BlocksTile,
             BlocksVisibility,
             Door,
+            OpensDoors,
+            UsesItems,
+            Boss,
+            BashesDoors,
+            WantsToOpenDoor,
+            WantsToCloseDoor,
+            WantsToBashDoor,
             CombatStats,
+            NaturalAttacks,
             SufferDamage,
             WantsToMelee,
             Item,
@@ -154,10 +217,18 @@ pub fn load_game(ecs: &mut World) {
             AreaOfEffect,
             Confusion,
             ProvidesHealing,
+            PackMember,
+            Routed,
+            Ability,
             InBackpack,
             WantsToPickupItem,
             WantsToUseItem,
             WantsToDropItem,
+            WantsToThrowItem,
+            Teleports,
+            WantsToTeleport,
+            LightSource,
+            Fuel,
             WantsToRemoveItem,
             SerializationHelper,
             Equippable,
@@ -167,7 +238,34 @@ pub fn load_game(ecs: &mut World) {
             ParticleLifetime,
             HungerClock,
             ProvidesFood,
+            Perishable,
+            Sickness,
+            Value,
+            Gold,
+            Purse,
+            Vendor,
             MagicMapper,
+            IdentifiesItem,
+            Cursed,
+            CurseKnown,
+            RemovesCurse,
+            Charges,
+            Recharges,
+            Stackable,
+            Throwable,
+            Potion,
+            Weight,
+            Attributes,
+            Mana,
+            KnownSpell,
+            Spellbook,
+            Encumbrance,
+            Durability,
+            RepairsItem,
+            MagicItem,
+            ElementalDamage,
+            AttributeBonus,
+            Container,
             Hidden,
             EntryTrigger,
             EntityMoved,
@@ -185,6 +283,10 @@ pub fn load_game(ecs: &mut World) {
             let mut worldmap = ecs.write_resource::<super::map::Map>();
             *worldmap = h.map.clone();
             worldmap.tile_content = vec![Vec::new(); (worldmap.width * worldmap.height) as usize];
+            let mut uniques = ecs.write_resource::<UniqueTracker>();
+            *uniques = h.uniques.clone();
+            let mut identification = ecs.write_resource::<ItemIdentification>();
+            *identification = h.identification.clone();
             deleteme = Some(e);
         }
         for (e, _p, pos) in (&entities, &player, &position).join() {
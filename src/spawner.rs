@@ -1,20 +1,27 @@
 use super::{
-    random_table::RandomTable, AreaOfEffect, BlocksTile, BlocksVisibility, CombatStats, Confusion,
-    Consumable, DefenseBonus, Door, EntryTrigger, EquipmentSlot, Equippable, Hidden, HungerClock,
-    HungerState, InflictsDamage, Item, MagicMapper, Map, MeleePowerBonus, Monster, Name, Player,
-    Position, ProvidesFood, ProvidesHealing, Ranged, Rect, Renderable, SerializeMe,
-    SingleActivation, TileType, Viewshed,
+    inventory_system::stow_item, magic_items, navigation_system::NEIGHBOURS,
+    random_table::RandomTable, Ability, AiState, AreaOfEffect, AttackEffect, Attributes,
+    BashesDoors, Behavior, BlocksTile, BlocksVisibility, Boss, Charges, CombatStats, Confusion,
+    Consumable, Container, Cursed, DefenseBonus, Door, Durability, Encumbrance, EncumbranceTier,
+    EntryTrigger, EquipmentSlot, Equippable, Equipped, Faction, Fuel, Gold, Hidden, HungerClock,
+    HungerState, IdentifiesItem, InBackpack, InflictsDamage, Item, ItemIdentification,
+    KeepsDistance, KnownSpell, LightSource, MagicMapper, Mana, Map, MeleePowerBonus, Monster, Name,
+    NaturalAttack, NaturalAttacks, OpensDoors, PackMember, Perishable, Player, Position, Potion,
+    ProvidesFood, ProvidesHealing, Purse, Ranged, Recharges, Rect, RemovesCurse, Renderable,
+    RepairsItem, SerializeMe, SingleActivation, Spellbook, Stackable, TeleportKind, Teleports,
+    Throwable, TileType, UniqueTracker, UsesItems, Value, Vendor, Viewshed, Weight,
 };
 use rltk::{RandomNumberGenerator, RGB};
 use specs::prelude::*;
 use specs::saveload::{MarkedBuilder, SimpleMarker};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 const MAX_SPAWNS: i32 = 4;
 
 /// Spawns the player and return his/her entity object
 pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
-    ecs.create_entity()
+    let player = ecs
+        .create_entity()
         .with(Position {
             x: player_x,
             y: player_y,
@@ -26,6 +33,9 @@ pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
             render_order: 0,
         })
         .with(Player {})
+        .with(Faction {
+            name: "Player".to_string(),
+        })
         .with(Viewshed {
             visible_tiles: Vec::new(),
             range: 8,
@@ -42,16 +52,49 @@ pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
         })
         .with(HungerClock {
             state: HungerState::WellFed,
-            duration: 20,
+            nutrition: 620,
+        })
+        .with(Purse { gold: 0 })
+        .with(Attributes {
+            strength: 10,
+            intelligence: 10,
+        })
+        .with(Mana {
+            current: 20,
+            max: 20,
+            recovery: 0,
+        })
+        .with(Encumbrance {
+            carried: 0.0,
+            capacity: 0.0,
+            tier: EncumbranceTier::Unburdened,
+            lagging: false,
         })
         .marked::<SimpleMarker<SerializeMe>>()
-        .build()
+        .build();
+
+    // Nobody goes down there without a light
+    torch_builder(ecs)
+        .with(Equipped {
+            owner: player,
+            slot: EquipmentSlot::Light,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+
+    player
 }
 
 fn room_table(map_depth: i32) -> RandomTable {
     RandomTable::new()
         .add("Goblin", 10)
         .add("Orc", 1 + map_depth)
+        .add("Goblin Shaman", map_depth)
+        .add("Orc Archer", map_depth - 1)
+        .add("Wolf", map_depth - 1)
+        .add("Bear", map_depth - 3)
+        .add("Deer", 2)
+        .add("Goblin War Band", map_depth - 2)
         .add("Health Potion", 7)
         .add("Fireball Scroll", 2 + map_depth)
         .add("Confusion Scroll", 2 + map_depth)
@@ -61,8 +104,26 @@ fn room_table(map_depth: i32) -> RandomTable {
         .add("Longsword", map_depth - 1)
         .add("Tower Shield", map_depth - 1)
         .add("Rations", 10)
+        .add("Apple", 4)
         .add("Magic Mapping Scroll", 2)
+        .add("Identify Scroll", 3)
+        .add("Remove Curse Scroll", 2)
+        .add("Recharging Scroll", 1)
+        .add("Teleportation Scroll", 2)
+        .add("Blinking Scroll", 2)
+        .add("Level Teleport Scroll", map_depth - 2)
+        .add("Repair Kit", 2)
+        .add("Rock", 4)
+        .add("Torch", 3 + map_depth)
+        .add("Lantern", map_depth - 2)
+        .add("Glowing Fungus", map_depth)
+        .add("Magic Item", 1 + map_depth)
+        .add("Wand of Magic Missile", map_depth - 1)
+        .add("Wand of Fireball", map_depth - 3)
+        .add("Spellbook", map_depth)
         .add("Bear Trap", 2)
+        .add("Teleport Trap", map_depth - 1)
+        .add("Gold", 8)
 }
 
 /// Fills a room with stuff!
@@ -129,6 +190,79 @@ pub fn spawn_region(
     }
 }
 
+/// Scrolls and potions start out unidentified, so these are the names the player doesn't know
+const SCROLLS: &[&str] = &[
+    "Fireball Scroll",
+    "Confusion Scroll",
+    "Magic Missile Scroll",
+    "Scroll of Magic Mapping",
+    "Scroll of Identify",
+    "Scroll of Remove Curse",
+    "Scroll of Recharging",
+    "Scroll of Teleportation",
+    "Scroll of Blinking",
+    "Scroll of Level Teleport",
+];
+const POTIONS: &[&str] = &["Health Potion"];
+const WANDS: &[&str] = &["Wand of Magic Missile", "Wand of Fireball"];
+
+const SCROLL_SYLLABLES: &[&str] = &[
+    "KA", "ZU", "XY", "LOR", "MEK", "VAZ", "THU", "RIN", "GOL", "PYX", "ZZY", "QUO", "NEB", "FEH",
+];
+const POTION_LOOKS: &[&str] = &[
+    "murky",
+    "bubbling",
+    "fizzy",
+    "cloudy",
+    "glowing",
+    "viscous",
+    "smoky",
+    "swirling",
+    "oily",
+    "sparkling",
+];
+const WAND_MATERIALS: &[&str] = &["oak", "bone", "iron", "glass", "ivory", "copper", "ebony"];
+
+/// Makes up this run's names for every scroll, potion and wand
+pub fn item_identification(rng: &mut RandomNumberGenerator) -> ItemIdentification {
+    let mut identification = ItemIdentification::default();
+    let mut used: Vec<String> = Vec::new();
+
+    for scroll in SCROLLS.iter() {
+        let fake = loop {
+            let mut word = String::new();
+            for _i in 0..rng.roll_dice(1, 2) + 1 {
+                word +=
+                    SCROLL_SYLLABLES[rng.roll_dice(1, SCROLL_SYLLABLES.len() as i32) as usize - 1];
+            }
+            let fake = format!("scroll of {}", word);
+            if !used.contains(&fake) {
+                break fake;
+            }
+        };
+        used.push(fake.clone());
+        identification.obfuscated.insert(scroll.to_string(), fake);
+    }
+
+    let mut looks: Vec<&str> = POTION_LOOKS.to_vec();
+    for potion in POTIONS.iter() {
+        let look = looks.remove(rng.roll_dice(1, looks.len() as i32) as usize - 1);
+        identification
+            .obfuscated
+            .insert(potion.to_string(), format!("{} potion", look));
+    }
+
+    let mut materials: Vec<&str> = WAND_MATERIALS.to_vec();
+    for wand in WANDS.iter() {
+        let material = materials.remove(rng.roll_dice(1, materials.len() as i32) as usize - 1);
+        identification
+            .obfuscated
+            .insert(wand.to_string(), format!("{} wand", material));
+    }
+
+    identification
+}
+
 /// Spawns a named entity (name in tuple.1) at the location in (tuple.0)
 pub fn spawn_entity(ecs: &mut World, spawn: &(&usize, &String)) {
     let map = ecs.fetch::<Map>();
@@ -137,34 +271,495 @@ pub fn spawn_entity(ecs: &mut World, spawn: &(&usize, &String)) {
     let y = (*spawn.0 / width) as i32;
     std::mem::drop(map);
 
+    // Uniques only ever die once
+    if ecs.fetch::<UniqueTracker>().killed.contains(spawn.1) {
+        return;
+    }
+
     match spawn.1.as_ref() {
         "Goblin" => goblin(ecs, x, y),
+        "Goblin War Band" => goblin_war_band(ecs, x, y),
         "Orc" => orc(ecs, x, y),
+        "Goblin Shaman" => goblin_shaman(ecs, x, y),
+        "Orc Archer" => orc_archer(ecs, x, y),
+        "Wolf" => wolf(ecs, x, y),
+        "Bear" => bear(ecs, x, y),
+        "Deer" => deer(ecs, x, y),
         "Health Potion" => health_potion(ecs, x, y),
         "Fireball Scroll" => fireball_scroll(ecs, x, y),
         "Confusion Scroll" => confusion_scroll(ecs, x, y),
         "Magic Missile Scroll" => magic_missile_scroll(ecs, x, y),
-        "Dagger" => dagger(ecs, x, y),
-        "Shield" => shield(ecs, x, y),
-        "Longsword" => longsword(ecs, x, y),
-        "Tower Shield" => tower_shield(ecs, x, y),
+        "Dagger" => {
+            let cursed = roll_curse(ecs);
+            dagger(ecs, x, y, cursed)
+        }
+        "Shield" => {
+            let cursed = roll_curse(ecs);
+            shield(ecs, x, y, cursed)
+        }
+        "Longsword" => {
+            let cursed = roll_curse(ecs);
+            longsword(ecs, x, y, cursed)
+        }
+        "Tower Shield" => {
+            let cursed = roll_curse(ecs);
+            tower_shield(ecs, x, y, cursed)
+        }
         "Rations" => rations(ecs, x, y),
+        "Apple" => apple(ecs, x, y),
         "Magic Mapping Scroll" => magic_mapping_scroll(ecs, x, y),
+        "Identify Scroll" => identify_scroll(ecs, x, y),
+        "Remove Curse Scroll" => remove_curse_scroll(ecs, x, y),
+        "Recharging Scroll" => recharging_scroll(ecs, x, y),
+        "Teleportation Scroll" => teleportation_scroll(ecs, x, y),
+        "Blinking Scroll" => blinking_scroll(ecs, x, y),
+        "Level Teleport Scroll" => level_teleport_scroll(ecs, x, y),
+        "Repair Kit" => repair_kit(ecs, x, y),
+        "Rock" => rock(ecs, x, y),
+        "Torch" => torch(ecs, x, y),
+        "Lantern" => lantern(ecs, x, y),
+        "Glowing Fungus" => glowing_fungus(ecs, x, y),
+        "Magic Item" => magic_items::magic_item(ecs, x, y),
+        "Wand of Magic Missile" => magic_missile_wand(ecs, x, y),
+        "Wand of Fireball" => fireball_wand(ecs, x, y),
+        "Spellbook" => spellbook(ecs, x, y),
         "Bear Trap" => bear_trap(ecs, x, y),
+        "Teleport Trap" => teleport_trap(ecs, x, y),
         "Door" => door(ecs, x, y),
+        "Gold" => gold(ecs, x, y),
+        "Vendor" => vendor(ecs, x, y),
+        "Chest" => chest(ecs, x, y),
+        "Barrel" => barrel(ecs, x, y),
+        "Grubnak the Goblin King" => goblin_king(ecs, x, y),
+        "Ugra the Orc Warlord" => orc_warlord(ecs, x, y),
         _ => (),
     }
 }
 
 fn orc(ecs: &mut World, x: i32, y: i32) {
-    monster(ecs, x, y, rltk::to_cp437('o'), "Orc");
+    monster(ecs, x, y, rltk::to_cp437('o'), "Orc", "Orcs", 16, 1, 4)
+        .with(OpensDoors {})
+        .with(UsesItems {})
+        .build();
 }
 
 fn goblin(ecs: &mut World, x: i32, y: i32) {
-    monster(ecs, x, y, rltk::to_cp437('g'), "Goblin");
+    goblin_builder(ecs, x, y).build();
+}
+
+fn goblin_builder(ecs: &mut World, x: i32, y: i32) -> EntityBuilder<'_> {
+    monster(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('g'),
+        "Goblin",
+        "Goblins",
+        16,
+        1,
+        4,
+    )
+    .with(OpensDoors {})
+    .with(UsesItems {})
+}
+
+/// A war chief and a handful of goblins, spawned as a group around the chief
+fn goblin_war_band(ecs: &mut World, x: i32, y: i32) {
+    let leader = monster(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('G'),
+        "Goblin War Chief",
+        "Goblins",
+        24,
+        2,
+        5,
+    )
+    .with(OpensDoors {})
+    .with(UsesItems {})
+    .build();
+
+    let band_size = {
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        rng.roll_dice(1, 3) + 1
+    };
+    for idx in free_tiles_near(ecs, x, y, band_size as usize) {
+        let map = ecs.fetch::<Map>();
+        let (member_x, member_y) = (idx as i32 % map.width, idx as i32 / map.width);
+        std::mem::drop(map);
+        goblin_builder(ecs, member_x, member_y)
+            .with(PackMember { leader })
+            .build();
+    }
+}
+
+/// Finds up to `count` floor tiles that nothing is standing on yet, within a few steps of
+/// (x, y) without going through walls
+fn free_tiles_near(ecs: &World, x: i32, y: i32, count: usize) -> Vec<usize> {
+    let map = ecs.fetch::<Map>();
+    let positions = ecs.read_storage::<Position>();
+    let occupied: Vec<usize> = positions
+        .join()
+        .map(|pos| map.xy_idx(pos.x, pos.y))
+        .collect();
+
+    let mut free: Vec<usize> = Vec::new();
+    let mut visited: HashSet<usize> = HashSet::new();
+    let mut frontier = vec![map.xy_idx(x, y)];
+    visited.insert(frontier[0]);
+    for _step in 0..3 {
+        let mut next: Vec<usize> = Vec::new();
+        for idx in frontier.iter() {
+            let (cx, cy) = (*idx as i32 % map.width, *idx as i32 / map.width);
+            for (dx, dy) in NEIGHBOURS {
+                let (tx, ty) = (cx + dx, cy + dy);
+                if tx < 1 || tx > map.width - 1 || ty < 1 || ty > map.height - 1 {
+                    continue;
+                }
+                let neighbor = map.xy_idx(tx, ty);
+                if map.tiles[neighbor] == TileType::Wall || !visited.insert(neighbor) {
+                    continue;
+                }
+                if map.tiles[neighbor] == TileType::Floor && !occupied.contains(&neighbor) {
+                    free.push(neighbor);
+                    if free.len() >= count {
+                        return free;
+                    }
+                }
+                next.push(neighbor);
+            }
+        }
+        frontier = next;
+    }
+    free
+}
+
+fn goblin_shaman(ecs: &mut World, x: i32, y: i32) {
+    let shaman = monster(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('g'),
+        "Goblin Shaman",
+        "Goblins",
+        10,
+        0,
+        2,
+    )
+    .with(KeepsDistance { range: 3 })
+    .with(OpensDoors {})
+    .with(UsesItems {})
+    .with(LightSource {
+        color: RGB::named(rltk::MEDIUM_PURPLE),
+        range: 2,
+    })
+    .build();
+
+    ability(ecs, shaman, "Magic Missile", 6, 3)
+        .with(InflictsDamage { damage: 6 })
+        .build();
+    ability(ecs, shaman, "Confusion", 6, 8)
+        .with(Confusion { turns: 3 })
+        .build();
+}
+
+fn orc_archer(ecs: &mut World, x: i32, y: i32) {
+    let archer = monster(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('o'),
+        "Orc Archer",
+        "Orcs",
+        14,
+        0,
+        3,
+    )
+    .with(KeepsDistance { range: 2 })
+    .with(OpensDoors {})
+    .with(UsesItems {})
+    .build();
+
+    ability(ecs, archer, "Arrow", 8, 1)
+        .with(InflictsDamage { damage: 4 })
+        .build();
+}
+
+/// Bosses wait in their lair, awake, in place of the mood `monster` rolls for everyone else;
+/// they never wander off and fight to the death
+fn lair_guard() -> Behavior {
+    Behavior {
+        state: AiState::Idle,
+        wanders: false,
+        flee_below_percent: 0,
+        perception: 2,
+    }
+}
+
+fn goblin_king(ecs: &mut World, x: i32, y: i32) {
+    let king = monster(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('K'),
+        "Grubnak the Goblin King",
+        "Goblins",
+        45,
+        3,
+        7,
+    )
+    .with(Boss {})
+    .with(lair_guard())
+    .with(OpensDoors {})
+    .with(UsesItems {})
+    .build();
+
+    ability(ecs, king, "Thrown Spear", 5, 3)
+        .with(InflictsDamage { damage: 8 })
+        .build();
+    // A boss's prize is never cursed
+    let already_there = items_at(ecs, x, y);
+    longsword(ecs, x, y, false);
+    hand_over_new_items(ecs, king, x, y, &already_there);
+    give_items(
+        ecs,
+        king,
+        x,
+        y,
+        &[
+            "Health Potion".to_string(),
+            "Gold".to_string(),
+            "Gold".to_string(),
+        ],
+    );
+}
+
+fn orc_warlord(ecs: &mut World, x: i32, y: i32) {
+    let warlord = monster(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('W'),
+        "Ugra the Orc Warlord",
+        "Orcs",
+        70,
+        4,
+        9,
+    )
+    .with(NaturalAttacks {
+        attacks: vec![
+            natural_attack("axe", 10, 2),
+            NaturalAttack {
+                effect: Some(AttackEffect::Confusion { turns: 2 }),
+                ..natural_attack("shield bash", 3, 0)
+            },
+        ],
+    })
+    .with(Boss {})
+    .with(lair_guard())
+    .with(BashesDoors { strength: 8 })
+    .with(UsesItems {})
+    .build();
+
+    ability(ecs, warlord, "Javelin", 6, 4)
+        .with(InflictsDamage { damage: 10 })
+        .build();
+    // A boss's prize is never cursed
+    let already_there = items_at(ecs, x, y);
+    tower_shield(ecs, x, y, false);
+    hand_over_new_items(ecs, warlord, x, y, &already_there);
+    give_items(
+        ecs,
+        warlord,
+        x,
+        y,
+        &[
+            "Fireball Scroll".to_string(),
+            "Health Potion".to_string(),
+            "Gold".to_string(),
+            "Gold".to_string(),
+            "Gold".to_string(),
+        ],
+    );
+}
+
+fn wolf(ecs: &mut World, x: i32, y: i32) {
+    monster(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('w'),
+        "Wolf",
+        "Predators",
+        12,
+        1,
+        0,
+    )
+    .with(NaturalAttacks {
+        attacks: vec![natural_attack("bite", 5, 2)],
+    })
+    .build();
+}
+
+fn bear(ecs: &mut World, x: i32, y: i32) {
+    monster(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('B'),
+        "Bear",
+        "Predators",
+        30,
+        2,
+        0,
+    )
+    .with(NaturalAttacks {
+        attacks: vec![
+            natural_attack("bite", 6, 0),
+            natural_attack("claw", 4, 2),
+            natural_attack("claw", 4, 2),
+        ],
+    })
+    .with(BashesDoors { strength: 4 })
+    .build();
+}
+
+fn deer(ecs: &mut World, x: i32, y: i32) {
+    monster(ecs, x, y, rltk::to_cp437('d'), "Deer", "Wildlife", 8, 0, 2).build();
+}
+
+fn natural_attack<S: ToString>(name: S, damage: i32, hit_bonus: i32) -> NaturalAttack {
+    NaturalAttack {
+        name: name.to_string(),
+        damage,
+        hit_bonus,
+        effect: None,
+    }
+}
+
+/// Starts building a ranged ability for a monster, used through the same path as the player's items
+fn ability<S: ToString>(
+    ecs: &mut World,
+    owner: Entity,
+    name: S,
+    range: i32,
+    cooldown: i32,
+) -> EntityBuilder<'_> {
+    ecs.create_entity()
+        .with(Name {
+            name: name.to_string(),
+        })
+        .with(Ranged { range })
+        .with(Ability {
+            owner,
+            cooldown,
+            cooldown_remaining: 0,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+}
+
+/// Everything a spellbook can teach
+const SPELLS: [&str; 5] = ["Magic Missile", "Fireball", "Confusion", "Mending", "Blink"];
+
+/// Starts building a spell for its owner to cast, through the same path as the player's items
+fn spell_builder<'a>(
+    ecs: &'a mut World,
+    owner: Entity,
+    name: &str,
+    mana_cost: i32,
+) -> EntityBuilder<'a> {
+    ecs.create_entity()
+        .with(Name {
+            name: name.to_string(),
+        })
+        .with(KnownSpell { owner, mana_cost })
+        .marked::<SimpleMarker<SerializeMe>>()
+}
+
+/// Teaches `owner` the named spell
+pub fn spell(ecs: &mut World, owner: Entity, name: &str) {
+    match name {
+        "Magic Missile" => spell_builder(ecs, owner, name, 3)
+            .with(Ranged { range: 6 })
+            .with(InflictsDamage { damage: 6 })
+            .build(),
+        "Fireball" => spell_builder(ecs, owner, name, 8)
+            .with(Ranged { range: 6 })
+            .with(InflictsDamage { damage: 15 })
+            .with(AreaOfEffect { radius: 2 })
+            .build(),
+        "Confusion" => spell_builder(ecs, owner, name, 4)
+            .with(Ranged { range: 6 })
+            .with(Confusion { turns: 4 })
+            .build(),
+        "Mending" => spell_builder(ecs, owner, name, 5)
+            .with(ProvidesHealing { heal_amount: 6 })
+            .build(),
+        "Blink" => spell_builder(ecs, owner, name, 5)
+            .with(Ranged { range: 8 })
+            .with(Teleports {
+                kind: TeleportKind::Blink,
+            })
+            .build(),
+        _ => return,
+    };
+}
+
+fn spellbook(ecs: &mut World, x: i32, y: i32) {
+    let spell = {
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        SPELLS[rng.roll_dice(1, SPELLS.len() as i32) as usize - 1]
+    };
+
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('≡'),
+            fg: RGB::named(rltk::MEDIUM_PURPLE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: format!("Spellbook of {}", spell),
+        })
+        .with(Item {})
+        .with(Value { base: 60 })
+        .with(Weight { pounds: 3.0 })
+        .with(Spellbook {
+            spell: spell.to_string(),
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
 }
 
-fn monster<S: ToString>(ecs: &mut World, x: i32, y: i32, glyph: rltk::FontCharType, name: S) {
+/// Starts building a generic monster, leaving the caller to add anything special and build it
+#[allow(clippy::too_many_arguments)]
+fn monster<'a, S: ToString>(
+    ecs: &'a mut World,
+    x: i32,
+    y: i32,
+    glyph: rltk::FontCharType,
+    name: S,
+    faction: &str,
+    hp: i32,
+    defense: i32,
+    power: i32,
+) -> EntityBuilder<'a> {
+    let (state, wanders) = {
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        let state = match rng.roll_dice(1, 3) {
+            1 => AiState::Sleeping,
+            _ => AiState::Idle,
+        };
+        (state, rng.roll_dice(1, 2) == 1)
+    };
+    // Animals have keener senses than the humanoids
+    let perception = match faction {
+        "Predators" | "Wildlife" => 3,
+        _ => 0,
+    };
+
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
@@ -179,18 +774,26 @@ fn monster<S: ToString>(ecs: &mut World, x: i32, y: i32, glyph: rltk::FontCharTy
             dirty: true,
         })
         .with(Monster {})
+        .with(Faction {
+            name: faction.to_string(),
+        })
+        .with(Behavior {
+            state,
+            wanders,
+            flee_below_percent: 25,
+            perception,
+        })
         .with(Name {
             name: name.to_string(),
         })
         .with(BlocksTile {})
         .with(CombatStats {
-            max_hp: 16,
-            hp: 16,
-            defense: 1,
-            power: 4,
+            max_hp: hp,
+            hp,
+            defense,
+            power,
         })
         .marked::<SimpleMarker<SerializeMe>>()
-        .build();
 }
 
 fn health_potion(ecs: &mut World, x: i32, y: i32) {
@@ -206,7 +809,11 @@ fn health_potion(ecs: &mut World, x: i32, y: i32) {
             name: "Health Potion".to_string(),
         })
         .with(Item {})
+        .with(Stackable { quantity: 1 })
+        .with(Value { base: 20 })
+        .with(Weight { pounds: 0.5 })
         .with(Consumable {})
+        .with(Potion {})
         .with(ProvidesHealing { heal_amount: 8 })
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
@@ -225,6 +832,9 @@ fn magic_missile_scroll(ecs: &mut World, x: i32, y: i32) {
             name: "Magic Missile Scroll".to_string(),
         })
         .with(Item {})
+        .with(Stackable { quantity: 1 })
+        .with(Value { base: 30 })
+        .with(Weight { pounds: 0.5 })
         .with(Consumable {})
         .with(Ranged { range: 6 })
         .with(InflictsDamage { damage: 8 })
@@ -245,6 +855,9 @@ fn fireball_scroll(ecs: &mut World, x: i32, y: i32) {
             name: "Fireball Scroll".to_string(),
         })
         .with(Item {})
+        .with(Stackable { quantity: 1 })
+        .with(Value { base: 60 })
+        .with(Weight { pounds: 0.5 })
         .with(Consumable {})
         .with(Ranged { range: 6 })
         .with(InflictsDamage { damage: 20 })
@@ -266,6 +879,9 @@ fn confusion_scroll(ecs: &mut World, x: i32, y: i32) {
             name: "Confusion Scroll".to_string(),
         })
         .with(Item {})
+        .with(Stackable { quantity: 1 })
+        .with(Value { base: 40 })
+        .with(Weight { pounds: 0.5 })
         .with(Consumable {})
         .with(Ranged { range: 6 })
         .with(Confusion { turns: 4 })
@@ -273,8 +889,93 @@ fn confusion_scroll(ecs: &mut World, x: i32, y: i32) {
         .build();
 }
 
-fn dagger(ecs: &mut World, x: i32, y: i32) {
+/// One in this many pieces of equipment is cursed
+const CURSE_CHANCE: i32 = 8;
+
+pub fn roll_curse(ecs: &mut World) -> bool {
+    let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+    rng.roll_dice(1, CURSE_CHANCE) == 1
+}
+
+/// Cursed gear hinders its wearer as much as it would otherwise have helped
+pub fn cursed_bonus(bonus: i32, cursed: bool) -> i32 {
+    if cursed {
+        -bonus
+    } else {
+        bonus
+    }
+}
+
+pub fn curse(builder: EntityBuilder<'_>, cursed: bool) -> EntityBuilder<'_> {
+    if cursed {
+        builder.with(Cursed {})
+    } else {
+        builder
+    }
+}
+
+/// Starts building a torch, wherever it ends up
+fn torch_builder(ecs: &mut World) -> EntityBuilder<'_> {
+    ecs.create_entity()
+        .with(Renderable {
+            glyph: rltk::to_cp437('¡'),
+            fg: RGB::named(rltk::ORANGE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Torch".to_string(),
+        })
+        .with(Item {})
+        .with(Value { base: 5 })
+        .with(Weight { pounds: 1.0 })
+        .with(Equippable {
+            slot: EquipmentSlot::Light,
+        })
+        .with(LightSource {
+            color: RGB::from_f32(1.0, 0.7, 0.4),
+            range: 6,
+        })
+        .with(Fuel { turns: 500 })
+}
+
+fn torch(ecs: &mut World, x: i32, y: i32) {
+    torch_builder(ecs)
+        .with(Position { x, y })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn lantern(ecs: &mut World, x: i32, y: i32) {
     ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('♀'),
+            fg: RGB::named(rltk::YELLOW),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Lantern".to_string(),
+        })
+        .with(Item {})
+        .with(Value { base: 40 })
+        .with(Weight { pounds: 3.0 })
+        .with(Equippable {
+            slot: EquipmentSlot::Light,
+        })
+        .with(LightSource {
+            color: RGB::from_f32(1.0, 0.9, 0.7),
+            range: 8,
+        })
+        .with(Fuel { turns: 2000 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn dagger(ecs: &mut World, x: i32, y: i32, cursed: bool) {
+    let builder = ecs
+        .create_entity()
         .with(Position { x, y })
         .with(Renderable {
             glyph: rltk::to_cp437('/'),
@@ -286,16 +987,26 @@ fn dagger(ecs: &mut World, x: i32, y: i32) {
             name: "Dagger".to_string(),
         })
         .with(Item {})
+        .with(Value { base: 15 })
+        .with(Weight { pounds: 1.0 })
+        .with(Durability {
+            current: 20,
+            max: 20,
+        })
         .with(Equippable {
             slot: EquipmentSlot::Melee,
         })
-        .with(MeleePowerBonus { power: 2 })
+        .with(MeleePowerBonus {
+            power: cursed_bonus(2, cursed),
+        });
+    curse(builder, cursed)
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 }
 
-fn shield(ecs: &mut World, x: i32, y: i32) {
-    ecs.create_entity()
+fn shield(ecs: &mut World, x: i32, y: i32, cursed: bool) {
+    let builder = ecs
+        .create_entity()
         .with(Position { x, y })
         .with(Renderable {
             glyph: rltk::to_cp437('('),
@@ -307,16 +1018,26 @@ fn shield(ecs: &mut World, x: i32, y: i32) {
             name: "Shield".to_string(),
         })
         .with(Item {})
+        .with(Value { base: 15 })
+        .with(Weight { pounds: 10.0 })
+        .with(Durability {
+            current: 25,
+            max: 25,
+        })
         .with(Equippable {
             slot: EquipmentSlot::Shield,
         })
-        .with(DefenseBonus { defense: 1 })
+        .with(DefenseBonus {
+            defense: cursed_bonus(1, cursed),
+        });
+    curse(builder, cursed)
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 }
 
-fn longsword(ecs: &mut World, x: i32, y: i32) {
-    ecs.create_entity()
+fn longsword(ecs: &mut World, x: i32, y: i32, cursed: bool) {
+    let builder = ecs
+        .create_entity()
         .with(Position { x, y })
         .with(Renderable {
             glyph: rltk::to_cp437('/'),
@@ -328,16 +1049,26 @@ fn longsword(ecs: &mut World, x: i32, y: i32) {
             name: "Longsword".to_string(),
         })
         .with(Item {})
+        .with(Value { base: 50 })
+        .with(Weight { pounds: 4.0 })
+        .with(Durability {
+            current: 40,
+            max: 40,
+        })
         .with(Equippable {
             slot: EquipmentSlot::Melee,
         })
-        .with(MeleePowerBonus { power: 4 })
+        .with(MeleePowerBonus {
+            power: cursed_bonus(4, cursed),
+        });
+    curse(builder, cursed)
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 }
 
-fn tower_shield(ecs: &mut World, x: i32, y: i32) {
-    ecs.create_entity()
+fn tower_shield(ecs: &mut World, x: i32, y: i32, cursed: bool) {
+    let builder = ecs
+        .create_entity()
         .with(Position { x, y })
         .with(Renderable {
             glyph: rltk::to_cp437('('),
@@ -349,10 +1080,19 @@ fn tower_shield(ecs: &mut World, x: i32, y: i32) {
             name: "Tower Shield".to_string(),
         })
         .with(Item {})
+        .with(Value { base: 50 })
+        .with(Weight { pounds: 35.0 })
+        .with(Durability {
+            current: 50,
+            max: 50,
+        })
         .with(Equippable {
             slot: EquipmentSlot::Shield,
         })
-        .with(DefenseBonus { defense: 3 })
+        .with(DefenseBonus {
+            defense: cursed_bonus(3, cursed),
+        });
+    curse(builder, cursed)
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 }
@@ -370,12 +1110,65 @@ fn rations(ecs: &mut World, x: i32, y: i32) {
             name: "Rations".to_string(),
         })
         .with(Item {})
-        .with(ProvidesFood {})
+        .with(Stackable { quantity: 1 })
+        .with(Value { base: 5 })
+        .with(Weight { pounds: 2.0 })
+        .with(ProvidesFood { nutrition: 400 })
         .with(Consumable {})
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 }
 
+fn apple(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('%'),
+            fg: RGB::named(rltk::RED),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Apple".to_string(),
+        })
+        .with(Item {})
+        .with(Value { base: 2 })
+        .with(Weight { pounds: 0.5 })
+        .with(ProvidesFood { nutrition: 120 })
+        .with(Perishable { turns: 400 })
+        .with(Consumable {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+/// A fallen creature's body, which makes a meal for anyone who isn't fussy and holds
+/// whatever it was carrying. Bigger creatures are more filling, and heavier to carry.
+pub fn corpse(ecs: &mut World, x: i32, y: i32, name: &str, max_hp: i32) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('%'),
+            fg: RGB::named(rltk::INDIANRED),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 3,
+        })
+        .with(Name {
+            name: format!("{} Corpse", name),
+        })
+        .with(Item {})
+        .with(Weight {
+            pounds: max_hp as f32,
+        })
+        .with(ProvidesFood {
+            nutrition: max_hp * 10,
+        })
+        .with(Perishable { turns: 150 })
+        .with(Consumable {})
+        .with(Container {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build()
+}
+
 fn magic_mapping_scroll(ecs: &mut World, x: i32, y: i32) {
     ecs.create_entity()
         .with(Position { x, y })
@@ -389,12 +1182,214 @@ fn magic_mapping_scroll(ecs: &mut World, x: i32, y: i32) {
             name: "Scroll of Magic Mapping".to_string(),
         })
         .with(Item {})
+        .with(Stackable { quantity: 1 })
+        .with(Value { base: 50 })
+        .with(Weight { pounds: 0.5 })
         .with(MagicMapper {})
         .with(Consumable {})
         .marked::<SimpleMarker<SerializeMe>>()
         .build();
 }
 
+fn teleport_scroll(ecs: &mut World, x: i32, y: i32, name: &str, kind: TeleportKind) {
+    let mut builder = ecs
+        .create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(')'),
+            fg: RGB::named(rltk::MAGENTA),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: name.to_string(),
+        })
+        .with(Item {})
+        .with(Stackable { quantity: 1 })
+        .with(Value { base: 40 })
+        .with(Weight { pounds: 0.5 })
+        .with(Teleports { kind })
+        .with(Consumable {});
+    if kind == TeleportKind::Blink {
+        builder = builder.with(Ranged { range: 8 });
+    }
+    builder.marked::<SimpleMarker<SerializeMe>>().build();
+}
+
+fn teleportation_scroll(ecs: &mut World, x: i32, y: i32) {
+    teleport_scroll(ecs, x, y, "Scroll of Teleportation", TeleportKind::Random);
+}
+
+fn blinking_scroll(ecs: &mut World, x: i32, y: i32) {
+    teleport_scroll(ecs, x, y, "Scroll of Blinking", TeleportKind::Blink);
+}
+
+fn level_teleport_scroll(ecs: &mut World, x: i32, y: i32) {
+    teleport_scroll(ecs, x, y, "Scroll of Level Teleport", TeleportKind::Level);
+}
+
+fn identify_scroll(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(')'),
+            fg: RGB::named(rltk::WHITE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Scroll of Identify".to_string(),
+        })
+        .with(Item {})
+        .with(Stackable { quantity: 1 })
+        .with(Value { base: 25 })
+        .with(Weight { pounds: 0.5 })
+        .with(IdentifiesItem {})
+        .with(Consumable {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn remove_curse_scroll(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(')'),
+            fg: RGB::named(rltk::WHITE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Scroll of Remove Curse".to_string(),
+        })
+        .with(Item {})
+        .with(Stackable { quantity: 1 })
+        .with(Value { base: 40 })
+        .with(Weight { pounds: 0.5 })
+        .with(RemovesCurse {})
+        .with(Consumable {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn recharging_scroll(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(')'),
+            fg: RGB::named(rltk::WHITE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Scroll of Recharging".to_string(),
+        })
+        .with(Item {})
+        .with(Stackable { quantity: 1 })
+        .with(Value { base: 50 })
+        .with(Weight { pounds: 0.5 })
+        .with(Recharges {})
+        .with(Consumable {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn repair_kit(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('&'),
+            fg: RGB::named(rltk::BURLYWOOD),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Repair Kit".to_string(),
+        })
+        .with(Item {})
+        .with(Stackable { quantity: 1 })
+        .with(Value { base: 30 })
+        .with(Weight { pounds: 2.0 })
+        .with(RepairsItem {})
+        .with(Consumable {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn rock(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('*'),
+            fg: RGB::named(rltk::GREY),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Rock".to_string(),
+        })
+        .with(Item {})
+        .with(Stackable { quantity: 1 })
+        .with(Value { base: 1 })
+        .with(Weight { pounds: 1.0 })
+        .with(Throwable { damage: 2 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn roll_charges(ecs: &mut World, n: i32, die: i32, bonus: i32) -> Charges {
+    let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+    let max = rng.roll_dice(n, die) + bonus;
+    Charges { current: max, max }
+}
+
+fn magic_missile_wand(ecs: &mut World, x: i32, y: i32) {
+    let charges = roll_charges(ecs, 1, 4, 2);
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('-'),
+            fg: RGB::named(rltk::CYAN),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Wand of Magic Missile".to_string(),
+        })
+        .with(Item {})
+        .with(Value { base: 80 })
+        .with(Weight { pounds: 1.0 })
+        .with(charges)
+        .with(Ranged { range: 6 })
+        .with(InflictsDamage { damage: 8 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn fireball_wand(ecs: &mut World, x: i32, y: i32) {
+    let charges = roll_charges(ecs, 1, 3, 1);
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('-'),
+            fg: RGB::named(rltk::ORANGE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Wand of Fireball".to_string(),
+        })
+        .with(Item {})
+        .with(Value { base: 150 })
+        .with(Weight { pounds: 1.0 })
+        .with(charges)
+        .with(Ranged { range: 6 })
+        .with(InflictsDamage { damage: 20 })
+        .with(AreaOfEffect { radius: 3 })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
 fn bear_trap(ecs: &mut World, x: i32, y: i32) {
     ecs.create_entity()
         .with(Position { x, y })
@@ -415,6 +1410,288 @@ fn bear_trap(ecs: &mut World, x: i32, y: i32) {
         .build();
 }
 
+fn teleport_trap(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('^'),
+            fg: RGB::named(rltk::MAGENTA),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Teleport Trap".to_string(),
+        })
+        .with(Hidden {})
+        .with(EntryTrigger {})
+        .with(Teleports {
+            kind: TeleportKind::Random,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn glowing_fungus(ecs: &mut World, x: i32, y: i32) {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('"'),
+            fg: RGB::named(rltk::LIME_GREEN),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 3,
+        })
+        .with(Name {
+            name: "Glowing Fungus".to_string(),
+        })
+        .with(LightSource {
+            color: RGB::from_f32(0.3, 0.9, 0.5),
+            range: 3,
+        })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn gold(ecs: &mut World, x: i32, y: i32) {
+    let amount = {
+        let depth = ecs.fetch::<Map>().depth;
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        rng.roll_dice(2, 6) + depth * 3
+    };
+
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('$'),
+            fg: RGB::named(rltk::GOLD),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Gold".to_string(),
+        })
+        .with(Item {})
+        .with(Gold { amount })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+}
+
+fn vendor_table() -> RandomTable {
+    RandomTable::new()
+        .add("Health Potion", 6)
+        .add("Rations", 4)
+        .add("Magic Missile Scroll", 3)
+        .add("Confusion Scroll", 2)
+        .add("Fireball Scroll", 1)
+        .add("Identify Scroll", 3)
+        .add("Remove Curse Scroll", 2)
+        .add("Recharging Scroll", 1)
+        .add("Teleportation Scroll", 2)
+        .add("Blinking Scroll", 1)
+        .add("Repair Kit", 2)
+        .add("Torch", 3)
+        .add("Lantern", 1)
+        .add("Magic Item", 2)
+        .add("Wand of Magic Missile", 1)
+        .add("Wand of Fireball", 1)
+        .add("Spellbook", 1)
+        .add("Dagger", 2)
+        .add("Shield", 2)
+        .add("Longsword", 1)
+        .add("Tower Shield", 1)
+}
+
+fn vendor(ecs: &mut World, x: i32, y: i32) {
+    let (price_percent, stock) = {
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        let table = vendor_table();
+        let stock_count = rng.roll_dice(1, 3) + 3;
+        let stock: Vec<String> = (0..stock_count).map(|_| table.roll(&mut rng)).collect();
+        (80 + rng.roll_dice(1, 7) * 10, stock)
+    };
+
+    let vendor = ecs
+        .create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('@'),
+            fg: RGB::named(rltk::GOLD),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 1,
+        })
+        .with(Name {
+            name: "Shopkeeper".to_string(),
+        })
+        .with(BlocksTile {})
+        .with(Vendor { price_percent })
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+
+    give_items(ecs, vendor, x, y, &stock);
+}
+
+fn chest_table(map_depth: i32) -> RandomTable {
+    RandomTable::new()
+        .add("Gold", 6)
+        .add("Health Potion", 4)
+        .add("Magic Item", 2 + map_depth)
+        .add("Fireball Scroll", 2)
+        .add("Identify Scroll", 2)
+        .add("Remove Curse Scroll", 1)
+        .add("Recharging Scroll", 1)
+        .add("Blinking Scroll", 1)
+        .add("Level Teleport Scroll", map_depth - 2)
+        .add("Wand of Magic Missile", map_depth - 1)
+        .add("Wand of Fireball", map_depth - 3)
+        .add("Spellbook", 2)
+}
+
+fn barrel_table() -> RandomTable {
+    RandomTable::new()
+        .add("Rations", 6)
+        .add("Apple", 3)
+        .add("Rock", 3)
+        .add("Torch", 2)
+        .add("Health Potion", 2)
+        .add("Repair Kit", 1)
+        .add("Gold", 1)
+}
+
+/// One chest in this many is rigged with a needle trap
+const CHEST_TRAP_CHANCE: i32 = 5;
+
+fn chest(ecs: &mut World, x: i32, y: i32) {
+    let (trap, loot) = {
+        let depth = ecs.fetch::<Map>().depth;
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        let table = chest_table(depth);
+        let loot_count = rng.roll_dice(1, 3) + 1;
+        let loot: Vec<String> = (0..loot_count).map(|_| table.roll(&mut rng)).collect();
+        let trap = if rng.roll_dice(1, CHEST_TRAP_CHANCE) == 1 {
+            Some(4 + depth)
+        } else {
+            None
+        };
+        (trap, loot)
+    };
+
+    let mut builder = ecs
+        .create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('■'),
+            fg: RGB::named(rltk::GOLDENROD),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Chest".to_string(),
+        })
+        .with(BlocksTile {})
+        .with(Container {});
+    if let Some(damage) = trap {
+        builder = builder.with(InflictsDamage { damage });
+    }
+    let chest = builder.marked::<SimpleMarker<SerializeMe>>().build();
+
+    give_items(ecs, chest, x, y, &loot);
+}
+
+fn barrel(ecs: &mut World, x: i32, y: i32) {
+    let contents = {
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        let table = barrel_table();
+        let count = rng.roll_dice(1, 3) - 1;
+        (0..count)
+            .map(|_| table.roll(&mut rng))
+            .collect::<Vec<String>>()
+    };
+
+    let barrel = ecs
+        .create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('○'),
+            fg: RGB::named(rltk::BURLYWOOD),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Name {
+            name: "Barrel".to_string(),
+        })
+        .with(BlocksTile {})
+        .with(Container {})
+        .marked::<SimpleMarker<SerializeMe>>()
+        .build();
+
+    give_items(ecs, barrel, x, y, &contents);
+}
+
+/// Spawns the named items at (x, y), then hands them all over to `owner`
+fn give_items(ecs: &mut World, owner: Entity, x: i32, y: i32, item_names: &[String]) {
+    let already_there = items_at(ecs, x, y);
+    let idx = ecs.fetch::<Map>().xy_idx(x, y);
+    for item in item_names.iter() {
+        spawn_entity(ecs, &(&idx, item));
+    }
+    hand_over_new_items(ecs, owner, x, y, &already_there);
+}
+
+/// Every item lying at (x, y)
+fn items_at(ecs: &World, x: i32, y: i32) -> Vec<Entity> {
+    let entities = ecs.entities();
+    let items = ecs.read_storage::<Item>();
+    let positions = ecs.read_storage::<Position>();
+    (&entities, &items, &positions)
+        .join()
+        .filter(|(_entity, _item, pos)| pos.x == x && pos.y == y)
+        .map(|(entity, _item, _pos)| entity)
+        .collect()
+}
+
+/// Moves the items at (x, y) into `owner`'s backpack, leaving alone anything that was
+/// `already_there` before they were spawned. Identical items pile onto one stack.
+fn hand_over_new_items(ecs: &mut World, owner: Entity, x: i32, y: i32, already_there: &[Entity]) {
+    let goods: Vec<Entity> = items_at(ecs, x, y)
+        .into_iter()
+        .filter(|item| !already_there.contains(item))
+        .collect();
+    for item in goods {
+        ecs.write_storage::<Position>().remove(item);
+        stow_item(ecs, owner, item);
+    }
+}
+
+/// Takes one item off a stack in someone's backpack, so it can be dropped, thrown or sold
+/// on its own. Returns the single item, which is the item itself if it wasn't stacked up.
+pub fn split_stack(ecs: &mut World, item: Entity) -> Entity {
+    match ecs.write_storage::<Stackable>().get_mut(item) {
+        Some(stack) if stack.quantity > 1 => stack.quantity -= 1,
+        _ => return item,
+    }
+    let name = ecs
+        .read_storage::<Name>()
+        .get(item)
+        .unwrap()
+        .name
+        .to_string();
+    let owner = ecs.read_storage::<InBackpack>().get(item).unwrap().owner;
+    let pos = ecs.read_storage::<Position>().get(owner).unwrap().clone();
+
+    // Stackable items are all alike, so a freshly spawned one is as good as a copy
+    let already_there = items_at(ecs, pos.x, pos.y);
+    let idx = ecs.fetch::<Map>().xy_idx(pos.x, pos.y);
+    spawn_entity(ecs, &(&idx, &name));
+    let single = items_at(ecs, pos.x, pos.y)
+        .into_iter()
+        .find(|new_item| !already_there.contains(new_item))
+        .expect("Unable to split stack");
+    ecs.write_storage::<Position>().remove(single);
+    ecs.write_storage::<InBackpack>()
+        .insert(single, InBackpack { owner })
+        .expect("Unable to insert backpack entry");
+    single
+}
+
 fn door(ecs: &mut World, x: i32, y: i32) {
     ecs.create_entity()
         .with(Position { x, y })
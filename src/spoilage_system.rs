@@ -0,0 +1,66 @@
+use super::{
+    gamelog::GameLog, inventory_system::spill_contents, InBackpack, ItemIdentification, Name,
+    Perishable, Position, RunState,
+};
+use specs::prelude::*;
+
+/// Rotten food left lying around for this many turns rots away to nothing
+const ROTS_AWAY_AFTER: i32 = 300;
+
+/// Ages perishable food by a turn each time the player acts
+pub struct SpoilageSystem {}
+
+impl<'a> System<'a> for SpoilageSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, RunState>,
+        WriteExpect<'a, GameLog>,
+        ReadExpect<'a, ItemIdentification>,
+        WriteStorage<'a, Perishable>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, InBackpack>,
+        ReadStorage<'a, Name>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            player_entity,
+            runstate,
+            mut log,
+            identification,
+            mut perishable,
+            mut positions,
+            mut backpack,
+            names,
+        ) = data;
+
+        if *runstate != RunState::PlayerTurn {
+            return;
+        }
+
+        for (entity, food) in (&entities, &mut perishable).join() {
+            food.turns -= 1;
+            let carried = backpack
+                .get(entity)
+                .is_some_and(|pack| pack.owner == *player_entity);
+            if food.turns == 0 && carried {
+                if let Some(name) = names.get(entity) {
+                    log.entries.push(format!(
+                        "Your {} has gone off.",
+                        identification.display_name(&name.name)
+                    ));
+                }
+            }
+            if food.turns <= -ROTS_AWAY_AFTER {
+                // Anything a corpse was holding is left behind when it's gone
+                if let Some(pos) = positions.get(entity).cloned() {
+                    spill_contents(&entities, &mut backpack, &mut positions, entity, &pos);
+                    entities.delete(entity).expect("Delete failed");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use super::{
+    gamelog::GameLog, map_builders::is_boss_lair, particle_system::ParticleBuilder, BlocksTile,
+    Boss, Map, Name, Position, RunState, TeleportKind, Viewshed, WantsToTeleport,
+};
+use rltk::{Point, RandomNumberGenerator};
+use specs::prelude::*;
+
+/// How many random spots to try before a random teleport gives up
+const RANDOM_TRIES: i32 = 1000;
+
+/// Moves anyone who wants to teleport, keeping the map's index and the player's position
+/// up to date so later systems see them where they landed
+pub struct TeleportSystem {}
+
+impl<'a> System<'a> for TeleportSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, Map>,
+        WriteExpect<'a, Point>,
+        WriteExpect<'a, RunState>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, ParticleBuilder>,
+        WriteStorage<'a, WantsToTeleport>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, Viewshed>,
+        ReadStorage<'a, BlocksTile>,
+        ReadStorage<'a, Name>,
+        ReadStorage<'a, Boss>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            player_entity,
+            mut map,
+            mut player_pos,
+            mut runstate,
+            mut log,
+            mut rng,
+            mut particle_builder,
+            mut wants_teleport,
+            mut positions,
+            mut viewsheds,
+            blockers,
+            names,
+            bosses,
+        ) = data;
+
+        for (entity, teleport) in (&entities, &wants_teleport).join() {
+            let from = match positions.get(entity) {
+                None => continue,
+                Some(pos) => Point::new(pos.x, pos.y),
+            };
+            let is_player = entity == *player_entity;
+
+            let destination = match teleport.kind {
+                TeleportKind::Level => {
+                    // Only the player can change levels; a living boss holds them to this one
+                    if is_player {
+                        if (&bosses).join().next().is_some() {
+                            log.entries
+                                .push("Something holds you to this level.".to_string());
+                        } else {
+                            let change = [-1, 1, 1, 2][rng.roll_dice(1, 4) as usize - 1];
+                            let depth = if map.depth + change < 1 {
+                                map.depth + 1
+                            } else {
+                                map.depth + change
+                            };
+                            // Never fly past a boss lair; its boss has to be beaten first
+                            let depth = (map.depth + 1..depth)
+                                .find(|d| is_boss_lair(*d))
+                                .unwrap_or(depth);
+                            *runstate = RunState::TeleportLevel { depth };
+                        }
+                    }
+                    continue;
+                }
+                // The player doesn't block the map, so make sure nobody lands on them
+                TeleportKind::Blink => teleport.destination.filter(|dest| {
+                    let idx = map.xy_idx(dest.x, dest.y);
+                    !map.blocked[idx] && *dest != *player_pos
+                }),
+                TeleportKind::Random => random_open_tile(&map, &mut rng, *player_pos),
+            };
+            let destination = match destination {
+                None => {
+                    if is_player {
+                        log.entries
+                            .push("You feel a brief lurch, but stay put.".to_string());
+                    }
+                    continue;
+                }
+                Some(destination) => destination,
+            };
+
+            // Keep the spatial index honest until it's next rebuilt
+            let from_idx = map.xy_idx(from.x, from.y);
+            let to_idx = map.xy_idx(destination.x, destination.y);
+            map.tile_content[from_idx].retain(|e| *e != entity);
+            map.tile_content[to_idx].push(entity);
+            if blockers.get(entity).is_some() {
+                map.blocked[from_idx] = map.tile_content[from_idx]
+                    .iter()
+                    .any(|e| blockers.get(*e).is_some());
+                map.blocked[to_idx] = true;
+            }
+
+            if let Some(pos) = positions.get_mut(entity) {
+                pos.x = destination.x;
+                pos.y = destination.y;
+            }
+            if let Some(viewshed) = viewsheds.get_mut(entity) {
+                viewshed.dirty = true;
+            }
+            if is_player {
+                *player_pos = destination;
+                log.entries
+                    .push("The world blurs, and you are elsewhere.".to_string());
+            } else if let Some(name) = names.get(entity) {
+                let seen = viewsheds
+                    .get(*player_entity)
+                    .is_some_and(|vs| vs.visible_tiles.contains(&from));
+                if seen {
+                    log.entries.push(format!("{} vanishes!", name.name));
+                }
+            }
+
+            for point in [from, destination] {
+                particle_builder.request(
+                    point.x,
+                    point.y,
+                    rltk::RGB::named(rltk::MAGENTA),
+                    rltk::RGB::named(rltk::BLACK),
+                    rltk::to_cp437('☼'),
+                    200.0,
+                );
+            }
+        }
+
+        wants_teleport.clear();
+    }
+}
+
+/// Picks a random tile that nothing (the player included) is standing on
+fn random_open_tile(map: &Map, rng: &mut RandomNumberGenerator, player: Point) -> Option<Point> {
+    (0..RANDOM_TRIES).find_map(|_| {
+        let x = rng.roll_dice(1, map.width - 2);
+        let y = rng.roll_dice(1, map.height - 2);
+        let idx = map.xy_idx(x, y);
+        if map.blocked[idx] || Point::new(x, y) == player {
+            None
+        } else {
+            Some(Point::new(x, y))
+        }
+    })
+}
@@ -0,0 +1,238 @@
+use super::{
+    gamelog::GameLog,
+    noise_system::{NoiseBuilder, COMBAT_VOLUME, DOOR_VOLUME},
+    particle_system::ParticleBuilder,
+    CombatStats, Confusion, InBackpack, InflictsDamage, ItemIdentification, Map, MeleePowerBonus,
+    Name, Position, Potion, ProvidesHealing, SufferDamage, Throwable, TileType, WantsToThrowItem,
+};
+use rltk::Point;
+use specs::prelude::*;
+
+/// How far (in tiles) anything can be thrown
+pub const THROW_RANGE: i32 = 6;
+
+/// Thrown potions splash everything this close to where they land
+const SPLASH_RADIUS: i32 = 1;
+
+pub struct ItemThrowSystem {}
+
+impl<'a> System<'a> for ItemThrowSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, Map>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, rltk::RandomNumberGenerator>,
+        WriteExpect<'a, ParticleBuilder>,
+        WriteExpect<'a, NoiseBuilder>,
+        WriteExpect<'a, ItemIdentification>,
+        WriteStorage<'a, WantsToThrowItem>,
+        WriteStorage<'a, Position>,
+        WriteStorage<'a, InBackpack>,
+        ReadStorage<'a, Name>,
+        WriteStorage<'a, CombatStats>,
+        WriteStorage<'a, SufferDamage>,
+        ReadStorage<'a, Throwable>,
+        ReadStorage<'a, MeleePowerBonus>,
+        ReadStorage<'a, ProvidesHealing>,
+        ReadStorage<'a, Potion>,
+        ReadStorage<'a, InflictsDamage>,
+        WriteStorage<'a, Confusion>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            player_entity,
+            map,
+            mut log,
+            mut rng,
+            mut particle_builder,
+            mut noise_builder,
+            mut identification,
+            mut wants_throw,
+            mut positions,
+            mut backpack,
+            names,
+            mut combat_stats,
+            mut suffer_damage,
+            throwable,
+            melee_bonuses,
+            healing,
+            potions,
+            inflicts_damage,
+            mut confused,
+        ) = data;
+
+        for (entity, throw) in (&entities, &wants_throw).join() {
+            let start = match positions.get(entity) {
+                None => continue,
+                Some(pos) => Point::new(pos.x, pos.y),
+            };
+            let throws = if entity == *player_entity {
+                "You throw".to_string()
+            } else {
+                format!(
+                    "{} throws",
+                    names.get(entity).map_or("Something", |n| n.name.as_str())
+                )
+            };
+            let item_name = identification.display_name(&names.get(throw.item).unwrap().name);
+
+            // The item flies until it reaches the target or hits a wall
+            let mut landing = start;
+            for step in rltk::line2d(rltk::LineAlg::Bresenham, start, throw.target)
+                .iter()
+                .skip(1)
+            {
+                let idx = map.xy_idx(step.x, step.y);
+                if map.tiles[idx] == TileType::Wall {
+                    break;
+                }
+                if map.blocked[idx] {
+                    // A creature in the way takes the hit; anything else (a closed door,
+                    // say) just stops it short
+                    let creature = map.tile_content[idx]
+                        .iter()
+                        .any(|e| combat_stats.get(*e).is_some());
+                    if creature {
+                        landing = *step;
+                    }
+                    break;
+                }
+                landing = *step;
+            }
+            let landing_idx = map.xy_idx(landing.x, landing.y);
+            let victim = map.tile_content[landing_idx]
+                .iter()
+                .find(|e| **e != entity && combat_stats.get(**e).is_some())
+                .copied();
+
+            backpack.remove(throw.item);
+
+            // Potions shatter and splash whoever is nearby with everything they do
+            if potions.get(throw.item).is_some() {
+                log.entries
+                    .push(format!("{} the {}. It shatters!", throws, item_name));
+                let real_name = names.get(throw.item).unwrap().name.to_string();
+                if entity == *player_entity && identification.identify(&real_name) {
+                    log.entries
+                        .push(format!("You discover that this is a {}.", real_name));
+                }
+                for dx in -SPLASH_RADIUS..=SPLASH_RADIUS {
+                    for dy in -SPLASH_RADIUS..=SPLASH_RADIUS {
+                        let (x, y) = (landing.x + dx, landing.y + dy);
+                        if x < 1 || x > map.width - 1 || y < 1 || y > map.height - 1 {
+                            continue;
+                        }
+                        let idx = map.xy_idx(x, y);
+                        for mob in map.tile_content[idx].iter() {
+                            let stats = match combat_stats.get_mut(*mob) {
+                                None => continue,
+                                Some(stats) => stats,
+                            };
+                            let splashed = if *mob == *player_entity {
+                                "You are splashed".to_string()
+                            } else {
+                                format!(
+                                    "{} is splashed",
+                                    names.get(*mob).map_or("Something", |n| n.name.as_str())
+                                )
+                            };
+                            if let Some(potion) = healing.get(throw.item) {
+                                stats.hp = i32::min(stats.max_hp, stats.hp + potion.heal_amount);
+                                log.entries.push(format!(
+                                    "{}, healing {} hp.",
+                                    splashed, potion.heal_amount
+                                ));
+                                particle_builder.request(
+                                    x,
+                                    y,
+                                    rltk::RGB::named(rltk::GREEN),
+                                    rltk::RGB::named(rltk::BLACK),
+                                    rltk::to_cp437('♥'),
+                                    200.0,
+                                );
+                            }
+                            if let Some(damage) = inflicts_damage.get(throw.item) {
+                                SufferDamage::new_damage(&mut suffer_damage, *mob, damage.damage);
+                                log.entries
+                                    .push(format!("{}, taking {} hp.", splashed, damage.damage));
+                                particle_builder.request(
+                                    x,
+                                    y,
+                                    rltk::RGB::named(rltk::RED),
+                                    rltk::RGB::named(rltk::BLACK),
+                                    rltk::to_cp437('‼'),
+                                    200.0,
+                                );
+                            }
+                            if let Some(confusion) = confused.get(throw.item).cloned() {
+                                confused
+                                    .insert(*mob, confusion)
+                                    .expect("Unable to insert status");
+                                log.entries.push(format!("{}, and reels!", splashed));
+                                particle_builder.request(
+                                    x,
+                                    y,
+                                    rltk::RGB::named(rltk::MAGENTA),
+                                    rltk::RGB::named(rltk::BLACK),
+                                    rltk::to_cp437('?'),
+                                    200.0,
+                                );
+                            }
+                        }
+                    }
+                }
+                noise_builder.request(landing.x, landing.y, DOOR_VOLUME);
+                entities.delete(throw.item).expect("Delete failed");
+                continue;
+            }
+
+            match victim {
+                Some(victim) => {
+                    let base = throwable.get(throw.item).map_or_else(
+                        || melee_bonuses.get(throw.item).map_or(0, |bonus| bonus.power),
+                        |thrown| thrown.damage,
+                    );
+                    let damage = base + rng.roll_dice(1, 4);
+                    log.entries.push(format!(
+                        "{} the {} at {}, hitting for {} hp.",
+                        throws,
+                        item_name,
+                        names.get(victim).map_or("something", |n| n.name.as_str()),
+                        damage
+                    ));
+                    SufferDamage::new_damage(&mut suffer_damage, victim, damage);
+                    particle_builder.request(
+                        landing.x,
+                        landing.y,
+                        rltk::RGB::named(rltk::ORANGE),
+                        rltk::RGB::named(rltk::BLACK),
+                        rltk::to_cp437('‼'),
+                        200.0,
+                    );
+                    noise_builder.request(landing.x, landing.y, COMBAT_VOLUME);
+                }
+                None => {
+                    log.entries.push(format!("{} the {}.", throws, item_name));
+                    // The clatter is as good a distraction as any
+                    noise_builder.request(landing.x, landing.y, DOOR_VOLUME);
+                }
+            }
+
+            positions
+                .insert(
+                    throw.item,
+                    Position {
+                        x: landing.x,
+                        y: landing.y,
+                    },
+                )
+                .expect("Unable to insert position");
+        }
+
+        wants_throw.clear();
+    }
+}
@@ -1,6 +1,7 @@
 use super::{
     gamelog::GameLog, particle_system::ParticleBuilder, EntityMoved, EntryTrigger, Hidden,
-    InflictsDamage, Map, Name, Position, SingleActivation, SufferDamage,
+    InflictsDamage, Map, Name, Position, SingleActivation, SufferDamage, Teleports,
+    WantsToTeleport,
 };
 use specs::prelude::*;
 
@@ -21,6 +22,8 @@ impl<'a> System<'a> for TriggerSystem {
         WriteExpect<'a, ParticleBuilder>,
         WriteStorage<'a, SufferDamage>,
         ReadStorage<'a, SingleActivation>,
+        ReadStorage<'a, Teleports>,
+        WriteStorage<'a, WantsToTeleport>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -37,6 +40,8 @@ impl<'a> System<'a> for TriggerSystem {
             mut particle_builder,
             mut inflict_damage,
             single_activation,
+            teleports,
+            mut wants_teleport,
         ) = data;
 
         // Iterate the entities that moved and their final position
@@ -76,6 +81,19 @@ impl<'a> System<'a> for TriggerSystem {
                                 );
                             }
 
+                            // Teleport traps send the victim somewhere else entirely
+                            if let Some(teleport) = teleports.get(*entity_id) {
+                                wants_teleport
+                                    .insert(
+                                        entity,
+                                        WantsToTeleport {
+                                            kind: teleport.kind,
+                                            destination: None,
+                                        },
+                                    )
+                                    .expect("Unable to insert intent");
+                            }
+
                             // If it is single activation, it needs to be removed
                             let sa = single_activation.get(*entity_id);
                             if let Some(_sa) = sa {
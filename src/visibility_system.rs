@@ -53,6 +53,11 @@ impl<'a> System<'a> for VisibilitySystem {
                 // If this is the player, reveal what they can see
                 let p: Option<&Player> = player.get(ent);
                 if let Some(_p) = p {
+                    // The player only makes out lit tiles, though they always know where
+                    // they're standing
+                    viewshed.visible_tiles.retain(|t| {
+                        (t.x == pos.x && t.y == pos.y) || map.is_lit(map.xy_idx(t.x, t.y))
+                    });
                     for t in map.visible_tiles.iter_mut() {
                         *t = false;
                     }